@@ -1,3 +1,46 @@
+use ankra::{ AnkraEngine, AnkraConfig };
+use std::process::exit;
+
+const USAGE: &str = "usage: ankra-cli lookup <text>";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(|a| &**a).collect::<Vec<_>>()[..] {
+        ["lookup", text] => lookup(text),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    }
+}
+
+// print how to type `text` in every installed layout, those failing to load are reported
+fn lookup(text: &str) {
+    let cfg = AnkraConfig { data_dir: None, ..AnkraConfig::default() };
+    let layouts = cfg.installed_layouts();
+    if layouts.is_empty() {
+        eprintln!("no layout installed in {}", cfg.dir.display());
+        exit(1);
+    }
+
+    let mut found = false;
+    for id in layouts {
+        let engine = match AnkraEngine::new(AnkraConfig { id: id.clone(), data_dir: None, ..AnkraConfig::default() }) {
+            Ok(engine) => engine,
+            Err(e) => {
+                eprintln!("{}: {}", id, e);
+                continue
+            }
+        };
+
+        for (id, sequences) in engine.lookup(text) {
+            found = true;
+            println!("{}: {}", id, sequences.join(" "));
+        }
+    }
+
+    if !found {
+        eprintln!("no layout has a sequence for {}", text);
+        exit(1);
+    }
 }
@@ -12,7 +12,9 @@ use wayland_protocols::misc::zwp_input_method_v2::client::{
 use zwp_virtual_keyboard::virtual_keyboard_unstable_v1::zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1;
 
 pub struct AnkraContext {
-    pub engine: AnkraEngine,
+    // `None` when the layout failed to load, keys are then passed through
+    pub engine: Option<AnkraEngine>,
+    layout: String,
     current_state: InputMethodState,
     vk: Main<ZwpVirtualKeyboardV1>,
     im: Main<ZwpInputMethodV2>,
@@ -29,6 +31,10 @@ pub struct AnkraContext {
 //     // LALT = 0x8,
 // }
 
+// modifiers picking a shift level rather than leaving keys to the client
+const SHIFT: u32 = 0x1;
+const ALT_GR: u32 = 0x80;
+
 #[derive(PartialEq, Eq)]
 pub enum InputMethodState {
     Active,
@@ -65,10 +71,8 @@ impl PressState {
 impl AnkraContext {
     pub fn new(layout: &str, vk: Main<ZwpVirtualKeyboardV1>, im: Main<ZwpInputMethodV2>, timer: TimerFd) -> Self { 
         Self {
-            engine: AnkraEngine::new(AnkraConfig { 
-                id: String::from(layout),
-                ..AnkraConfig::default() 
-            }),
+            engine: Self::load(layout),
+            layout: String::from(layout),
 
             current_state: InputMethodState::Inactive,
             serial: 0,
@@ -81,6 +85,40 @@ impl AnkraContext {
         }
     }
 
+    // every installed layout is loaded to switch to, starting with `layout`
+    fn load(layout: &str) -> Option<AnkraEngine> {
+        let mut cfg = AnkraConfig { 
+            id: String::from(layout),
+            candidate_list: true,
+            ..AnkraConfig::default() 
+        };
+
+        cfg.layouts = cfg.installed_layouts();
+        AnkraEngine::new(cfg).map_err(|e| log::error!("Failed to load layout `{}`, passing keys through: {}", layout, e)).ok()
+    }
+
+    // the files of a layout changed, drops any composition in progress
+    pub fn reload(&mut self) {
+        match &mut self.engine {
+            Some(engine) => {
+                if let Err(e) = engine.reload() {
+                    log::error!("Failed to reload layouts, keeping the previous ones: {}", e);
+                    return
+                }
+            },
+
+            None => {
+                self.engine = Self::load(&self.layout);
+                if self.engine.is_none() {
+                    return
+                }
+            }
+        }
+
+        log::info!("Reloaded layouts");
+        self.handle_response(AnkraResponse::Empty);
+    }
+
     pub fn new_data<'a>(data: &'a mut DispatchData) -> &'a mut Self {
         data.get::<Self>().unwrap()
     }
@@ -100,16 +138,16 @@ impl AnkraContext {
                 panic!("unavailable")
             },
 
-            ImEvent::Done => {
-                if self.current_state==InputMethodState::Inactive {
-                    // Focus lost, reset states
-                    self.engine.reset();
+            ImEvent::Done if self.current_state==InputMethodState::Inactive => {
+                // Focus lost, reset states
+                if let Some(engine) = &mut self.engine {
+                    engine.reset();
+                }
 
-                    // Input deactivated, stop repeating
-                    self.timer.disarm().unwrap();
-                    if let Some((_, ref mut press_state)) = self.repeat_state {
-                         *press_state = PressState::NotPressing
-                    }
+                // Input deactivated, stop repeating
+                self.timer.disarm().unwrap();
+                if let Some((_, ref mut press_state)) = self.repeat_state {
+                     *press_state = PressState::NotPressing
                 }
             },
 
@@ -129,34 +167,19 @@ impl AnkraContext {
             },
 
             KeyEvent::Key { state, key, time, .. } => {
-                if self.current_state==InputMethodState::Active && self.mod_state {
-                    match state {
+                match &mut self.engine {
+                    Some(engine) if self.current_state==InputMethodState::Active && self.mod_state => match state {
                         KeyState::Pressed => {
-                            match self.engine.on_key_press((key + 8) as u16) {
-                                AnkraResponse::Empty => {
-                                    self.im.set_preedit_string(String::new(), -1, -1);
-                                },
-
-                                AnkraResponse::Undefined => {
-                                    self.vk.key(time, key, state as _);
-                                    self.im.set_preedit_string(String::new(), -1, -1);
-                                    return
-                                },
-
-                                AnkraResponse::Commit(s) => { 
-                                    self.im.commit_string(s);
-                                    self.im.set_preedit_string(String::new(), -1, -1);
-                                },
-
-                                AnkraResponse::Suggest(s) => {
-                                    let len = s.len();
-                                    self.im.set_preedit_string(s, 0, len as _);
-                                }
+                            let response = engine.on_key_press((key + 8) as u16);
+                            if !self.handle_response(response) {
+                                self.vk.key(time, key, state as _);
+                                self.im.set_preedit_string(String::new(), -1, -1);
+                                // Clears a hint left by the previous commit
+                                self.im.commit(self.serial);
+                                self.serial += 1;
+                                return
                             }
 
-                            self.im.commit(self.serial);
-                            self.serial += 1;
-
                             match self.repeat_state {
                                 Some((info, ref mut press_state)) if !press_state.is_pressing(key) => {
                                     let duration = Duration::from_millis(info.delay as u64);
@@ -182,23 +205,32 @@ impl AnkraContext {
                                 }
                             }
 
+                            // eg. a completed chord
+                            let response = engine.on_key_release((key + 8) as u16);
+                            self.handle_response(response);
                             self.vk.key(time, key, state as _);
                         },
 
                         _ => {}
-                    }
-                } else {
-                    self.vk.key(time, key, state as _);
+                    },
+
+                    _ => self.vk.key(time, key, state as _),
                 }
             },
 
             KeyEvent::Modifiers { mods_depressed, mods_latched, mods_locked, group, .. } => {
                 self.mod_state = true;
+                let levels = (mods_depressed | mods_latched) & (SHIFT | ALT_GR);
 
-                if mods_depressed!=0 || mods_latched !=0 || mods_locked !=0 {
+                if (mods_depressed | mods_latched) & !levels !=0 || mods_locked !=0 {
                     self.mod_state = false;
                 }
 
+                let level = (levels & SHIFT !=0) as u16 + 2*(levels & ALT_GR !=0) as u16;
+                if let Some(engine) = &mut self.engine {
+                    engine.set_level(level);
+                }
+
                 self.vk.modifiers(mods_depressed, mods_latched, mods_locked, group);
             },
 
@@ -217,6 +249,51 @@ impl AnkraContext {
         }
     }
 
+    // show what the engine responded with, false when the key is left to the client instead
+    fn handle_response(&mut self, response: AnkraResponse) -> bool {
+        match response {
+            AnkraResponse::Undefined => return false,
+            AnkraResponse::Empty => {
+                self.im.set_preedit_string(String::new(), -1, -1);
+            },
+
+            AnkraResponse::Commit(s) => { 
+                self.im.commit_string(s);
+
+                // eg. 想 = dup, until the next key
+                let hint = self.engine.as_ref().and_then(|e| e.hint()).map(|h| h.to_string()).unwrap_or_default();
+                self.im.set_preedit_string(hint, -1, -1);
+            },
+
+            // the composition goes on right after the commit
+            AnkraResponse::Continue(s, response) => {
+                self.im.commit_string(s);
+                if self.handle_response(*response) {
+                    return true
+                }
+
+                // the key is sent after the committed text
+                self.im.commit(self.serial);
+                self.serial += 1;
+                return false
+            },
+
+            AnkraResponse::Suggest(s) => {
+                let len = s.len();
+                self.im.set_preedit_string(s, 0, len as _);
+            },
+
+            AnkraResponse::Candidates(c) => {
+                let (begin, end) = c.preedit.cursor.map(|(b, e)| (b as i32, e as i32)).unwrap_or((-1, -1));
+                self.im.set_preedit_string(c.preedit.text(), begin, end);
+            }
+        }
+
+        self.im.commit(self.serial);
+        self.serial += 1;
+        true
+    }
+
     pub fn handle_timer_ev(&mut self) -> std::io::Result<()> {
         // Read timer, this MUST be called or timer will be broken
         let overrun_count = self.timer.read()?;
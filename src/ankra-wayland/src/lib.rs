@@ -1,6 +1,11 @@
 mod context;
 use context::AnkraContext;
 
+use ankra::AnkraConfig;
+use inotify::{ Inotify, WatchMask };
+use std::ffi::OsStr;
+use std::os::unix::io::AsRawFd;
+
 use mio::{ unix::SourceFd, Events as MioEvents, Interest, Poll, Token };
 use mio_timerfd::{ ClockId, TimerFd };
 
@@ -27,11 +32,19 @@ pub struct State {
     context: AnkraContext,
     display: Display,
     event_queue: EventQueue,
-    poll: Poll
+    poll: Poll,
+    inotify: Inotify,
 }
 
 const POLL_WAYLAND: Token = Token(0);
 const POLL_TIMER: Token = Token(1);
+const POLL_INOTIFY: Token = Token(2);
+
+// swap and backup files of editors, any other file in a layout's folder may be read by it,
+// eg. charsets or conversion dictionaries
+fn is_temporary(name: &str) -> bool {
+    name.starts_with('.') || name.ends_with('~') || name.ends_with(".swp") || name.ends_with(".swx") || name=="4913"
+}
 
 impl State {
     pub fn new(id: &str) -> Self {
@@ -79,6 +92,19 @@ impl State {
         registry.register(&mut timer, POLL_TIMER, Interest::READABLE)
             .expect("Register timer to the epoll()");
 
+        // Watch the layouts for changes, editors often replace files rather than write to them
+        let mut inotify = Inotify::init().expect("Initialize inotify");
+        let config = AnkraConfig::default();
+        for layout in config.installed_layouts() {
+            let dir = config.dir.join(layout);
+            if let Err(e) = inotify.add_watch(&dir, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE) {
+                log::warn!("Failed to watch {}, changes to the layout won't be reloaded: {}", dir.display(), e);
+            }
+        }
+
+        registry.register(&mut SourceFd(&inotify.as_raw_fd()), POLL_INOTIFY, Interest::READABLE)
+            .expect("Register inotify to the epoll()");
+
         // Initialise context
         let mut context = AnkraContext::new(id, vk, im, timer);
         event_queue.sync_roundtrip(&mut context, |_, _, _| ()).unwrap();
@@ -88,8 +114,30 @@ impl State {
             display,
             event_queue,
             context,
-            poll
+            poll,
+            inotify,
+        }
+    }
+
+    fn handle_inotify_ev(&mut self) -> std::io::Result<()> {
+        let mut buffer = [0; 1024];
+        let mut changed = false;
+
+        // Drain every pending event, returns an empty iterator once there's none left
+        loop {
+            let mut events = self.inotify.read_events(&mut buffer)?.peekable();
+            if events.peek().is_none() {
+                break
+            }
+
+            changed |= events.any(|ev| matches!(ev.name.and_then(OsStr::to_str), Some(name) if !is_temporary(name)));
+        }
+
+        if changed {
+            self.context.reload();
         }
+
+        Ok(())
     }
 
     pub fn run(&mut self) {
@@ -115,6 +163,12 @@ impl State {
                         }
                     }
 
+                    POLL_INOTIFY => {
+                        if let Err(e) = self.handle_inotify_ev() {
+                            break 'main Err(e);
+                        }
+                    }
+
                     POLL_WAYLAND => {},
                     _ => unreachable!(),
                 }
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::fs::read_to_string;
+use crate::AnkraError;
+
+// a set of characters candidates may be limited to
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Set {
+    // a unicode block or any other run of codepoints, both ends included
+    Range(char, char),
+    // every character in the file, relative to the layout's folder, eg. a list of the characters in big5
+    File(String),
+}
+
+// characters within any of the configured sets
+#[derive(Default, Debug)]
+pub struct Charset {
+    ranges: Vec<(char, char)>,
+    chars: HashSet<char>,
+}
+
+impl Charset {
+    pub fn from_sets(sets: &[Set], dir: &Path) -> Result<Self, AnkraError> {
+        let mut charset = Charset::default();
+        for set in sets {
+            match set {
+                Set::Range(start, end) => charset.ranges.push((*start, *end)),
+                Set::File(file) => {
+                    let path = dir.join(file);
+                    let text = read_to_string(&path).map_err(|e| AnkraError::from(e).in_file(&path))?;
+                    charset.chars.extend(text.chars().filter(|c| !c.is_whitespace()));
+                }
+            }
+        }
+
+        Ok(charset)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty() && self.chars.is_empty()
+    }
+
+    // whether every character of `text` is in the set
+    pub fn contains(&self, text: &str) -> bool {
+        text.chars().all(|c| self.chars.contains(&c) || self.ranges.iter().any(|(start, end)| (*start..=*end).contains(&c)))
+    }
+}
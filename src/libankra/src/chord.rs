@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+use std::time::{ Duration, Instant };
+
+type KeyCode = u16;
+
+// keys pressed together, looked up as one code once all of them are released
+#[derive(Default, Debug)]
+pub struct Chord {
+    keys: BTreeSet<char>,
+    held: Vec<KeyCode>,
+    started: Option<Instant>,
+}
+
+impl Chord {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // whether a key pressed now comes too late to belong to the chord being built
+    pub fn is_late(&self, window: Duration) -> bool {
+        matches!(self.started, Some(started) if started.elapsed() > window)
+    }
+
+    pub fn is_held(&self, key_code: KeyCode) -> bool {
+        self.held.contains(&key_code)
+    }
+
+    pub fn press(&mut self, key_code: KeyCode, key: char) {
+        self.started.get_or_insert_with(Instant::now);
+        self.keys.insert(key);
+        self.held.push(key_code);
+    }
+
+    // whether the key was part of the chord, the chord is complete once nothing is held
+    pub fn release(&mut self, key_code: KeyCode) -> bool {
+        let held = self.held.len();
+        self.held.retain(|k| *k!=key_code);
+        held!=self.held.len()
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.held.is_empty()
+    }
+
+    // the keys in code order, leaving the chord empty
+    pub fn take(&mut self) -> String {
+        let code = self.keys.iter().collect();
+        self.clear();
+        code
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.held.clear();
+        self.started = None;
+    }
+}
+
+// chords are sets, so codes are compared with their keys in order
+pub fn normalise(sequence: &str) -> String {
+    sequence.chars().collect::<BTreeSet<char>>().into_iter().collect()
+}
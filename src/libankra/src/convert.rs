@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::fs::File;
+use std::io::{ BufRead, BufReader };
+use crate::AnkraError;
+
+// text conversion read from OpenCC dictionaries, eg. traditional to simplified
+#[derive(Default, Debug)]
+pub struct Converter {
+    map: HashMap<String, String>,
+    // characters in the longest text converted at once
+    longest: usize,
+}
+
+impl Converter {
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Self, AnkraError> {
+        let mut converter = Converter::default();
+        for path in paths {
+            let path = path.as_ref();
+            converter.read(path).map_err(|e| e.in_file(path))?;
+        }
+
+        Ok(converter)
+    }
+
+    // a text and its conversions separated by a tab on every line, only the first conversion is kept
+    fn read(&mut self, path: &Path) -> Result<(), AnkraError> {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if let Some((from, to)) = line.split_once('\t') {
+                if let Some(to) = to.split(' ').next() {
+                    self.longest = self.longest.max(from.chars().count());
+                    self.map.insert(from.to_string(), to.to_string());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // from left to right, the longest text found in the dictionaries is converted first
+    pub fn convert(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut converted = String::new();
+        let mut start = 0;
+
+        while start < chars.len() {
+            let found = (1..=self.longest.min(chars.len()-start)).rev().find_map(|len| {
+                let from: String = chars[start..start+len].iter().collect();
+                self.map.get(&from).map(|to| (len, to))
+            });
+
+            match found {
+                Some((len, to)) => {
+                    converted.push_str(to);
+                    start += len;
+                },
+
+                None => {
+                    converted.push(chars[start]);
+                    start += 1;
+                }
+            }
+        }
+
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn converter(pairs: &[(&str, &str)]) -> Converter {
+        let mut converter = Converter::default();
+        for (from, to) in pairs {
+            converter.longest = converter.longest.max(from.chars().count());
+            converter.map.insert(from.to_string(), to.to_string());
+        }
+
+        converter
+    }
+
+    #[test]
+    fn longest_match() {
+        let converter = converter(&[("頭", "头"), ("髮", "发"), ("頭髮", "頭发"), ("理髮店", "理发店")]);
+        assert_eq!(converter.convert("頭髮"), "頭发");
+        assert_eq!(converter.convert("頭頭髮"), "头頭发");
+        assert_eq!(converter.convert("理髮店的髮"), "理发店的发");
+        assert_eq!(converter.convert(""), "");
+    }
+}
@@ -0,0 +1,210 @@
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+use std::path::{ Path, PathBuf };
+use std::fs::{ File, create_dir_all, rename };
+use std::io::BufReader;
+use crate::AnkraError;
+use crate::table::Table;
+
+// share of a score kept per commit, older picks fade out as new ones are made
+const DECAY: f32 = 0.999;
+// scores below this are forgotten when saving
+const FORGET: f32 = 0.01;
+
+// learns which entries get committed for a typed key sequence
+#[derive(Default, Debug)]
+pub struct UserDict {
+    path: Option<PathBuf>,
+    clock: u64,
+    // learnt since last saved
+    dirty: bool,
+    usage: HashMap<String, HashMap<String, Usage>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Usage {
+    score: f32,
+    tick: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Record {
+    sequence: String,
+    character: String,
+    score: f32,
+    tick: u64,
+}
+
+impl UserDict {
+    // a file failing to read is started anew, learnt usage isn't worth failing the layout over
+    pub fn from_path(id: &str, base_dir: &Path) -> Self {
+        let path = base_dir.join(id).join("user").with_extension("csv");
+        let mut dict = UserDict { path: Some(path.clone()), ..Default::default() };
+
+        if path.exists() {
+            if let Err(e) = dict.read(&path) {
+                log::warn!("starting the user dictionary anew: {}", e.in_file(&path));
+                dict.usage.clear();
+                dict.clock = 0;
+            }
+        }
+
+        dict
+    }
+
+    fn read(&mut self, path: &Path) -> Result<(), AnkraError> {
+        let reader = BufReader::new(File::open(path)?);
+        for record in csv::Reader::from_reader(reader).deserialize() {
+            let record: Record = record?;
+            self.clock = self.clock.max(record.tick);
+            self.usage.entry(record.sequence)
+                .or_default()
+                .insert(record.character, Usage { score: record.score, tick: record.tick });
+        }
+
+        Ok(())
+    }
+
+    fn decayed(&self, usage: &Usage) -> f32 {
+        usage.score * DECAY.powf((self.clock - usage.tick) as f32)
+    }
+
+    // stable, entries never committed keep the order they were given in
+    pub fn sort(&self, indices: &mut [usize], table: &Table, key_sequence: &str) {
+        if let Some(usage) = self.usage.get(key_sequence) {
+            let score = |i: &usize| usage.get(&table.entries[*i].character).map(|u| self.decayed(u)).unwrap_or(0.0);
+            indices.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+    }
+
+    // kept in memory until saved
+    pub fn learn(&mut self, key_sequence: &str, character: &str) {
+        if self.path.is_none() {
+            return
+        }
+
+        self.clock += 1;
+        let score = self.usage.get(key_sequence)
+            .and_then(|usage| usage.get(character))
+            .map(|u| self.decayed(u))
+            .unwrap_or(0.0);
+
+        self.usage.entry(key_sequence.to_string())
+            .or_default()
+            .insert(character.to_string(), Usage { score: score + 1.0, tick: self.clock });
+        self.dirty = true;
+    }
+
+    // only once something was learnt, written aside then renamed over so a crash midway leaves the previous file whole
+    pub fn save(&mut self) -> Result<(), AnkraError> {
+        if !self.dirty {
+            return Ok(())
+        }
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+
+            let tmp = path.with_extension("csv.tmp");
+            let mut writer = csv::Writer::from_path(&tmp)?;
+            for (sequence, usage) in &self.usage {
+                for (character, u) in usage {
+                    let score = self.decayed(u);
+                    if score >= FORGET {
+                        writer.serialize(Record {
+                            sequence: sequence.clone(),
+                            character: character.clone(),
+                            score,
+                            tick: self.clock,
+                        })?;
+                    }
+                }
+            }
+
+            writer.flush()?;
+            drop(writer);
+            rename(&tmp, path)?;
+        }
+
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dict(name: &str) -> UserDict {
+        let dir = std::env::temp_dir().join("ankra-tests").join("dict");
+        let _ = std::fs::remove_file(dir.join(name).join("user.csv"));
+        UserDict::from_path(name, &dir)
+    }
+
+    fn score(dict: &UserDict, sequence: &str, character: &str) -> Option<f32> {
+        dict.usage.get(sequence)?.get(character).map(|u| dict.decayed(u))
+    }
+
+    #[test]
+    fn decay() {
+        let mut dict = dict("decay");
+        dict.learn("a", "日");
+        dict.learn("a", "日");
+        assert_eq!(score(&dict, "a", "日"), Some(1.0 + DECAY));
+
+        for _ in 0..1000 {
+            dict.learn("b", "月");
+        }
+
+        let faded = score(&dict, "a", "日").unwrap();
+        assert!((faded - (1.0 + DECAY) * DECAY.powi(1000)).abs() < 1e-4, "{}", faded);
+    }
+
+    #[test]
+    fn reload() {
+        let mut dict = dict("reload");
+        dict.learn("a", "日");
+        dict.learn("ab", "明");
+        dict.learn("ab", "明");
+        dict.save().unwrap();
+
+        let path = dict.path.clone().unwrap();
+        assert!(!path.with_extension("csv.tmp").exists());
+
+        let reloaded = UserDict::from_path("reload", path.parent().unwrap().parent().unwrap());
+        assert_eq!(reloaded.clock, dict.clock);
+        assert_eq!(score(&reloaded, "a", "日"), score(&dict, "a", "日"));
+        assert_eq!(score(&reloaded, "ab", "明"), score(&dict, "ab", "明"));
+    }
+
+    #[test]
+    fn forget() {
+        let mut dict = dict("forget");
+        dict.learn("a", "日");
+
+        // a single pick fades below FORGET after ln(0.01) / ln(0.999) ~ 4603 commits
+        for _ in 0..4700 {
+            dict.learn("b", "月");
+        }
+
+        dict.save().unwrap();
+        let path = dict.path.clone().unwrap();
+        let reloaded = UserDict::from_path("forget", path.parent().unwrap().parent().unwrap());
+        assert_eq!(score(&reloaded, "a", "日"), None);
+        assert!(score(&reloaded, "b", "月").is_some());
+    }
+
+    #[test]
+    fn corrupt() {
+        let dir = std::env::temp_dir().join("ankra-tests").join("dict");
+        create_dir_all(dir.join("corrupt")).unwrap();
+        std::fs::write(dir.join("corrupt").join("user.csv"), "sequence,character,score,tick\na,日,lots,1\n").unwrap();
+
+        let mut dict = UserDict::from_path("corrupt", &dir);
+        assert!(dict.usage.is_empty());
+
+        dict.learn("a", "日");
+        assert_eq!(score(&dict, "a", "日"), Some(1.0));
+    }
+}
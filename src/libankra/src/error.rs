@@ -1,4 +1,5 @@
 use thiserror::Error;
+use std::path::{ Path, PathBuf };
 
 #[derive(Error, Debug)]
 pub enum AnkraError {
@@ -9,5 +10,16 @@ pub enum AnkraError {
     #[error("`parsing error {0}`")]
     ZmeraldError(#[from] zmerald::error::SpannedError),
     #[error("kb parse error")]
-    KbParseError
-}
\ No newline at end of file
+    KbParseError,
+    #[error("`layout {0} is not loaded`")]
+    LayoutNotFound(String),
+    #[error("`{}`: {source}", .path.display())]
+    FileError { path: PathBuf, source: Box<AnkraError> },
+}
+
+impl AnkraError {
+    // point the error at the file it was read from
+    pub(crate) fn in_file(self, path: &Path) -> Self {
+        AnkraError::FileError { path: path.to_path_buf(), source: Box::new(self) }
+    }
+}
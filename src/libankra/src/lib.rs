@@ -2,54 +2,378 @@ mod error;
 pub use error::AnkraError;
 
 mod table;
+mod trie;
+mod dict;
+mod segment;
+mod chord;
+mod spec;
+mod convert;
+mod charset;
 use table::TableState;
+use spec::Spec;
 
 use std::path::PathBuf;
+use std::fs::{ create_dir_all, read_dir, write };
 
 pub struct AnkraEngine {
-    table: TableState,
+    // every loaded layout, keys go to the active one
+    tables: Vec<TableState>,
+    active: usize,
+    cfg: AnkraConfig,
+    // codes of the text committed by the last response, see `TableConfig::hint_layout`
+    hint: Option<Hint>,
+    // every key is left to the client while set, see TOGGLE_ASCII
+    ascii: bool,
+    // key bound to TOGGLE_ASCII pressed with no other key since, toggles once released
+    toggle: Option<u16>,
 }
 
 impl AnkraEngine {
-    pub fn new(mut cfg: AnkraConfig) -> Self {
+    pub fn new(mut cfg: AnkraConfig) -> Result<Self, AnkraError> {
         //rid id of non visible characters such as "\n"
         cfg.id.retain(|c| !c.is_whitespace());
-        let table = TableState::new(&cfg.id, &cfg.dir).unwrap();
 
-        AnkraEngine { table }
+        // no layout chosen yet, start on the first one installed
+        if cfg.id.is_empty() {
+            if let Some(id) = cfg.layouts.first() {
+                cfg.id = id.clone();
+            }
+        }
+
+        let mut ids = cfg.layouts.clone();
+        if !ids.contains(&cfg.id) {
+            ids.insert(0, cfg.id.clone());
+        }
+
+        // only the layout in use has to load, the others are left out with a warning
+        let mut tables = Vec::new();
+        for id in &ids {
+            match TableState::new(id, &cfg.dir, cfg.data_dir.as_deref()) {
+                Ok(table) => tables.push(table),
+                Err(e) if *id==cfg.id => return Err(e),
+                Err(e) => log::warn!("failed to load layout `{}`: {}", id, e),
+            }
+        }
+
+        let active = tables.iter().position(|t| t.table.id==cfg.id).unwrap_or(0);
+        Ok(AnkraEngine { tables, active, cfg, hint: None, ascii: false, toggle: None })
+    }
+
+    // read the files of every layout again, those failing to load stay as they were
+    pub fn reload(&mut self) -> Result<(), AnkraError> {
+        let mut result = Ok(());
+        for table in self.tables.iter_mut() {
+            match TableState::new(&table.table.id, &self.cfg.dir, self.cfg.data_dir.as_deref()) {
+                Ok(mut new) => {
+                    table.save();
+                    new.level = table.level;
+                    new.converting = table.converting;
+                    new.filtering = table.filtering;
+                    new.previous_character = std::mem::take(&mut table.previous_character);
+                    *table = new;
+                },
+
+                Err(e) => result = Err(e),
+            }
+        }
+
+        result
     }
 
     pub fn on_key_press(&mut self, key_code: u16) -> AnkraResponse {
-    	self.table.on_key_press(key_code)
+        let table = &self.tables[self.active];
+        let spec = table.config.keycode_to_spec(&key_code, table.level).cloned();
+        self.toggle = None;
+
+        // only tapped on its own, so it may be bound to a modifier such as Shift
+        if spec==Some(Spec::ToggleAscii) {
+            self.toggle = Some(key_code);
+            if self.ascii {
+                return AnkraResponse::Undefined
+            }
+
+            let response = self.tables[self.active].current();
+            return self.respond(response)
+        }
+
+        // layouts are left alone too
+        if self.ascii {
+            return AnkraResponse::Undefined
+        }
+
+        match spec {
+            Some(Spec::SwitchLayout(id)) => {
+                self.hint = None;
+                if let Err(e) = self.switch_layout(&id) {
+                    log::warn!("{}", e);
+                }
+
+                AnkraResponse::Empty
+            },
+
+            Some(Spec::CycleLayout) => {
+                self.hint = None;
+                self.cycle_layout();
+                AnkraResponse::Empty
+            },
+
+            _ => {
+                let response = self.tables[self.active].on_key_press(key_code);
+                self.respond(response)
+            }
+        }
     }
 
     pub fn on_key_release(&mut self, key_code: u16) -> AnkraResponse {    	
-    	self.table.on_key_release(key_code)
+        if self.toggle==Some(key_code) {
+            self.toggle = None;
+            self.ascii = !self.ascii;
+            if !self.ascii {
+                return AnkraResponse::Empty
+            }
+
+            let response = self.tables[self.active].finish();
+            return self.respond(response)
+        }
+
+    	let response = self.tables[self.active].on_key_release(key_code);
+    	self.respond(response)
+    }
+
+    // id of the active layout
+    pub fn layout(&self) -> &str {
+        &self.tables[self.active].table.id
+    }
+
+    // ids of the loaded layouts, in the order `cycle_layout` goes through them
+    pub fn layouts(&self) -> Vec<&str> {
+        self.tables.iter().map(|t| &*t.table.id).collect()
+    }
+
+    // how to type `text` in every loaded layout having a way to, shortest sequences first
+    pub fn lookup(&self, text: &str) -> Vec<(&str, Vec<&str>)> {
+        self.tables.iter()
+            .map(|t| (&*t.table.id, t.table.sequences(text)))
+            .filter(|(_, sequences)| !sequences.is_empty())
+            .collect()
+    }
+
+    pub fn switch_layout(&mut self, id: &str) -> Result<(), AnkraError> {
+        let index = self.tables.iter()
+            .position(|t| t.table.id==id)
+            .ok_or_else(|| AnkraError::LayoutNotFound(id.to_string()))?;
+
+        self.activate(index);
+        Ok(())
+    }
+
+    pub fn cycle_layout(&mut self) {
+        self.activate((self.active+1) % self.tables.len());
+    }
+
+    // drops whatever was composed in the previous layout, the new one is remembered in `data_dir`
+    fn activate(&mut self, index: usize) {
+        self.tables[self.active].reset();
+        self.tables[self.active].save();
+        self.active = index;
+
+        if let Some(dir) = &self.cfg.data_dir {
+            if let Err(e) = create_dir_all(dir).and_then(|_| write(dir.join("current_layout"), self.layout())) {
+                log::warn!("failed to save the current layout: {}", e);
+            }
+        }
+    }
+
+    // frontends not asking for a candidate list only get the selected candidate
+    fn respond(&mut self, response: AnkraResponse) -> AnkraResponse {
+        // looked up before conversion, as the hint layout lists the text as it was typed
+        let hint = match &response {
+            AnkraResponse::Commit(text) | AnkraResponse::Continue(text, _) => {
+                self.find_hint(text).map(|hint| Hint { text: self.tables[self.active].convert(text), ..hint })
+            },
+
+            _ => None,
+        };
+
+        let response = match self.tables[self.active].converted(response) {
+            AnkraResponse::Candidates(c) if !self.cfg.candidate_list => {
+                AnkraResponse::Suggest(c.text().map(str::to_string).unwrap_or_else(|| c.preedit.text()))
+            },
+
+            AnkraResponse::Continue(text, response) => AnkraResponse::Continue(text, Box::new(self.respond(*response))),
+
+            response => response
+        };
+
+        self.hint = hint;
+        response
     }
 
+    // how the text committed by the last response is typed in the active layout's `hint_layout`
+    pub fn hint(&self) -> Option<&Hint> {
+        self.hint.as_ref()
+    }
+
+    fn find_hint(&self, text: &str) -> Option<Hint> {
+        let id = self.tables[self.active].config.hint_layout.as_deref()?;
+        let table = &self.tables.iter().find(|t| t.table.id==id)?.table;
+        let sequences: Vec<String> = table.sequences(text).into_iter().map(str::to_string).collect();
+        if sequences.is_empty() {
+            return None
+        }
+
+        Some(Hint { text: text.to_string(), layout: id.to_string(), sequences })
+    }
+
+    // eg. once focus is lost, which is also when the user dictionary gets saved
     pub fn reset(&mut self) {
-    	self.table.reset();
+    	self.tables[self.active].reset();
+    	self.tables[self.active].save();
+    }
+
+    // 0 without modifiers, 1 with Shift, 2 with AltGr and 3 with both
+    pub fn set_level(&mut self, level: u16) {
+        for table in self.tables.iter_mut() {
+            table.level = level as usize;
+        }
     }
 }
 
-#[derive(Debug)]
+impl Drop for AnkraEngine {
+    fn drop(&mut self) {
+        for table in self.tables.iter_mut() {
+            table.save();
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum AnkraResponse {
     Commit(String),
     Suggest(String),
+    Candidates(Candidates), //only given when `AnkraConfig::candidate_list` is set, Suggest otherwise
+    Continue(String, Box<AnkraResponse>), //Commit followed by what the next composition shows, see `auto_select`
     Undefined, //Error
     Empty, //KeyCode found but didnt have anything to return eg. function keys
 }
 
+// eg. 想 = dup, for learning the codes of another layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub text: String,
+    pub layout: String,
+    // shortest first
+    pub sequences: Vec<String>,
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} = {}", self.text, self.sequences.join(" "))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Candidates {
+    // the current page
+    pub candidates: Vec<Candidate>,
+    // index of the selected candidate within the page
+    pub selected: usize,
+    // number of candidates across every page
+    pub total: usize,
+    pub key_sequence: String,
+    pub preedit: Preedit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub text: String,
+    pub label: String,
+    // keys still to type to reach this candidate
+    pub remaining: String,
+    // eg. its code in the main layout during a reverse lookup
+    pub annotation: String,
+}
+
+impl Candidates {
+    pub fn text(&self) -> Option<&str> {
+        self.candidates.get(self.selected).map(|c| &*c.text)
+    }
+}
+
+// what to show inline while composing, laid out by the layout's preedit template
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Preedit {
+    pub segments: Vec<PreeditSegment>,
+    // byte range of the text covered by the cursor, a caret when both ends are equal
+    pub cursor: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreeditSegment {
+    Keys(String),
+    Candidate(String),
+    Position(String), //eg. (2/7)
+    Text(String),
+}
+
+impl PreeditSegment {
+    pub fn as_str(&self) -> &str {
+        match self {
+            PreeditSegment::Keys(s) | PreeditSegment::Candidate(s) | PreeditSegment::Position(s) | PreeditSegment::Text(s) => s
+        }
+    }
+}
+
+impl Preedit {
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.as_str()).collect()
+    }
+
+    // length in bytes
+    pub(crate) fn len(&self) -> usize {
+        self.segments.iter().map(|s| s.as_str().len()).sum()
+    }
+
+    pub(crate) fn push(&mut self, segment: PreeditSegment) {
+        if !segment.as_str().is_empty() {
+            self.segments.push(segment);
+        }
+    }
+}
+
 pub struct AnkraConfig {
+    // the layout in use at first
     pub id: String,
-    pub dir: PathBuf
+    // other layouts to switch to, in the order CYCLE_LAYOUT goes through them
+    pub layouts: Vec<String>,
+    pub dir: PathBuf,
+    // where learnt candidate usage is kept, nothing is learnt when `None`
+    pub data_dir: Option<PathBuf>,
+    // respond with the whole page of candidates instead of only the selected one
+    pub candidate_list: bool,
 }
 
 impl Default for AnkraConfig {
     fn default() -> Self {
         AnkraConfig {
             dir: xdg::BaseDirectories::with_prefix("ankra").unwrap().get_config_home(),
-            id: "layout id was not defined".to_string()
+            data_dir: Some(xdg::BaseDirectories::with_prefix("ankra").unwrap().get_data_home()),
+            id: "layout id was not defined".to_string(),
+            layouts: Vec::new(),
+            candidate_list: false,
         }
     }
+}
+
+impl AnkraConfig {
+    // ids of the layouts found in `dir`, sorted
+    pub fn installed_layouts(&self) -> Vec<String> {
+        let mut ids: Vec<String> = read_dir(&self.dir).into_iter().flatten().flatten()
+            .filter(|entry| entry.path().join("config.zm").is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+
+        ids.sort();
+        ids
+    }
 }
\ No newline at end of file
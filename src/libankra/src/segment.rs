@@ -0,0 +1,75 @@
+use crate::table::Table;
+use std::cmp::Reverse;
+
+// a piece of a continuous run of keys, the code typed for it and the entry it resolved to
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub code: String,
+    pub entry: usize,
+}
+
+// fewer pieces first, then splits whose last piece is a complete code, then heavier entries
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Cost {
+    pieces: usize,
+    partial: bool,
+    weight: Reverse<u64>,
+}
+
+// split `keys` into table codes, only the last piece may be an unfinished code
+pub fn segment(table: &Table, keys: &str, rank: impl Fn(&mut [usize], &str)) -> Vec<Segment> {
+    let keys: Vec<char> = keys.chars().collect();
+
+    // best[end] is the cheapest split of keys[..end], as its cost, the start of its last piece and that piece
+    let mut best: Vec<Option<(Cost, usize, Segment)>> = vec![None; keys.len()+1];
+
+    for start in 0..keys.len() {
+        let cost = match &best[start] {
+            Some((cost, ..)) => *cost,
+            None if start==0 => Cost::default(),
+            None => continue,
+        };
+
+        for end in start+1..=keys.len() {
+            let code: String = keys[start..end].iter().collect();
+            if !table.has_prefix(&code) {
+                break
+            }
+
+            let (mut candidates, partial) = match table.exact(&code) {
+                [] if end==keys.len() => (table.lookup(&code), true),
+                [] => continue,
+                exact => (exact.to_vec(), false),
+            };
+
+            rank(&mut candidates, &code);
+            let entry = candidates[0];
+            let cost = Cost {
+                pieces: cost.pieces + 1,
+                partial,
+                weight: Reverse(cost.weight.0 + table.entries[entry].weight.unwrap_or(0) as u64),
+            };
+
+            // on a tie the later start wins, keeping earlier pieces as long as they were typed
+            if !matches!(&best[end], Some((c, ..)) if *c < cost) {
+                best[end] = Some((cost, start, Segment { code, entry }));
+            }
+        }
+    }
+
+    let mut segments = Vec::new();
+    let mut end = keys.len();
+    while end > 0 {
+        match best[end].take() {
+            Some((_, start, segment)) => {
+                segments.push(segment);
+                end = start;
+            },
+
+            None => return Vec::new()
+        }
+    }
+
+    segments.reverse();
+    segments
+}
@@ -0,0 +1,93 @@
+use serde::de::{ self, Deserialize, Deserializer, EnumAccess, VariantAccess, Visitor };
+use std::fmt;
+
+// functions a keycode can be bound to in a layout's config, unknown ones are rejected when it loads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Spec {
+    Commit,
+    // commit the key_sequence as typed
+    CommitRaw,
+    Next,
+    Prev,
+    PageUp,
+    PageDown,
+    Escape,
+    Backspace,
+    // stands for any single key
+    Wildcard,
+    // stands for any run of keys
+    WildcardAny,
+    // commit the previous text again
+    Repeat,
+    // pick a candidate of the current page, counting from 1
+    Select(usize),
+    // commit the text, after the current candidate when composing
+    Insert(String),
+    // make the layout with the given id the active one
+    SwitchLayout(String),
+    // move on to the next loaded layout
+    CycleLayout,
+    // leave every key to the client until tapped again
+    ToggleAscii,
+    // type the next code through the layout set as `reverse_lookup`
+    ReverseLookup,
+    // turn the `conversion` of committed text on or off
+    ToggleConversion,
+    // list either common characters only or all of them, see `charsets`
+    ToggleCharset,
+}
+
+const VARIANTS: &[&str] = &[
+    "COMMIT", "COMMIT_RAW", "NEXT", "PREV", "PAGE_UP", "PAGE_DOWN", "ESCAPE", "BACKSPACE", "WILDCARD", "WILDCARD_ANY",
+    "REPEAT", "SELECT", "INSERT", "SWITCH_LAYOUT", "CYCLE_LAYOUT", "TOGGLE_ASCII", "REVERSE_LOOKUP", "TOGGLE_CONVERSION",
+    "TOGGLE_CHARSET",
+];
+
+// the bare digits of configs written before SELECT, eg. `10: [ 1 ]`, still stand for SELECT(1) to SELECT(9)
+impl<'de> Deserialize<'de> for Spec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Spec", VARIANTS, SpecVisitor)
+    }
+}
+
+struct SpecVisitor;
+
+impl<'de> Visitor<'de> for SpecVisitor {
+    type Value = Spec;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a spec such as COMMIT or SELECT(1)")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Spec, A::Error> {
+        let (name, variant): (String, A::Variant) = data.variant()?;
+        let spec = match &*name {
+            "SELECT" => return variant.newtype_variant().map(Spec::Select),
+            "INSERT" => return variant.newtype_variant().map(Spec::Insert),
+            "SWITCH_LAYOUT" => return variant.newtype_variant().map(Spec::SwitchLayout),
+            "COMMIT" => Spec::Commit,
+            "COMMIT_RAW" => Spec::CommitRaw,
+            "NEXT" => Spec::Next,
+            "PREV" => Spec::Prev,
+            "PAGE_UP" => Spec::PageUp,
+            "PAGE_DOWN" => Spec::PageDown,
+            "ESCAPE" => Spec::Escape,
+            "BACKSPACE" => Spec::Backspace,
+            "WILDCARD" => Spec::Wildcard,
+            "WILDCARD_ANY" => Spec::WildcardAny,
+            "REPEAT" => Spec::Repeat,
+            "CYCLE_LAYOUT" => Spec::CycleLayout,
+            "TOGGLE_ASCII" => Spec::ToggleAscii,
+            "REVERSE_LOOKUP" => Spec::ReverseLookup,
+            "TOGGLE_CONVERSION" => Spec::ToggleConversion,
+            "TOGGLE_CHARSET" => Spec::ToggleCharset,
+            digit => match digit.parse::<usize>() {
+                Ok(n @ 1..=9) => Spec::Select(n),
+                _ => return Err(de::Error::unknown_variant(digit, VARIANTS)),
+            }
+        };
+
+        variant.unit_variant()?;
+        Ok(spec)
+    }
+}
@@ -1,166 +1,959 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
-use crate::{ AnkraError, AnkraResponse };
+use crate::{ AnkraError, AnkraResponse, Candidate, Candidates, Preedit, PreeditSegment };
+use crate::trie::Trie;
+use crate::dict::UserDict;
+use crate::segment::{ segment, Segment };
+use crate::chord::{ self, Chord };
+use crate::spec::Spec;
+use crate::convert::Converter;
+use crate::charset::{ self, Charset };
+use std::time::Duration;
 use std::fs::File;
 use std::io::BufReader;
 
 type KeyCode = u16;
 
+// stand-ins pushed to the key_sequence by the WILDCARD and WILDCARD_ANY specs
+pub const WILDCARD: char = '?';
+pub const WILDCARD_ANY: char = '*';
+
 #[derive(Default)]
 pub struct TableState {
 	pub table: Table,
     pub config: TableConfig,
+    pub dict: UserDict,
     pub key_sequence: String,
     pub index: usize,
-	pub relative_entries: Vec<Entry>,
-    pub previous_character: String
+	pub relative_entries: Vec<usize>,
+    // narrowed entries of every shorter key_sequence, popped on backspace
+    pub history: Vec<Vec<usize>>,
+    // best split of a key_sequence no single code starts with, see `TableConfig::segmentation`
+    pub sentence: Vec<Segment>,
+    // last committed text, kept across resets for REPEAT
+    pub previous_character: String,
+    // keys held down in chord mode, see `TableConfig::chord`
+    pub chord: Chord,
+    // shift level picking amongst the keys and specs bound to a keycode, 1 for Shift, 2 for AltGr
+    pub level: usize,
+    // layout typed through after REVERSE_LOOKUP, see `TableConfig::reverse_lookup`
+    pub reverse_lookup: Option<Box<TableState>>,
+    // keys go to `reverse_lookup` until it commits or is left
+    pub looking_up: bool,
+    // see `TableConfig::conversion`
+    pub converter: Converter,
+    // toggled by TOGGLE_CONVERSION
+    pub converting: bool,
+    // see `TableConfig::charsets`
+    pub charset: Charset,
+    // toggled by TOGGLE_CHARSET, between common characters only and all of them
+    pub filtering: bool,
 }
 
 
-//feature: copy previous character key bind, kinda like a repition mark, will need a var "previous character" buf in TableMethod
 impl TableState {
-    pub fn new(id: &str, path: &Path) -> Result<Self, AnkraError> {
+    pub fn new(id: &str, path: &Path, data_path: Option<&Path>) -> Result<Self, AnkraError> {
+        let mut state = Self::load(id, path, data_path)?;
+        if let Some(lookup) = &state.config.reverse_lookup {
+            // nothing typed through it is learnt, REVERSE_LOOKUP does nothing when it fails to load
+            match Self::load(lookup, path, None) {
+                Ok(table) => state.reverse_lookup = Some(Box::new(table)),
+                Err(e) => log::warn!("failed to load reverse lookup layout `{}`: {}", lookup, e),
+            }
+        }
+
+        Ok(state)
+    }
+
+    fn load(id: &str, path: &Path, data_path: Option<&Path>) -> Result<Self, AnkraError> {
+        let config = TableConfig::from_path(id, path)?;
+        let mut table = Table::from_path(id, path)?;
+        if config.chord {
+            table.normalise(chord::normalise);
+        }
+
+        let dictionaries: Vec<_> = config.conversion.iter().map(|p| path.join(id).join(p)).collect();
         Ok(Self {
-            table: Table::from_path(id, path)?,
-            config: TableConfig::from_path(id, path)?,
+            converter: Converter::from_paths(&dictionaries)?,
+            converting: true,
+            charset: Charset::from_sets(&config.charsets, &path.join(id))?,
+            filtering: true,
+            table,
+            config,
+            dict: data_path.map(|p| UserDict::from_path(id, p)).unwrap_or_default(),
             ..Default::default()
         })
     }
 
     pub fn on_key_press(&mut self, key_code: u16) -> AnkraResponse {
+        let spec = self.config.keycode_to_spec(&key_code, self.level).cloned();
+        if self.looking_up {
+            return self.press_lookup(key_code)
+        }
+
         let mut commit = false;
-    	match self.config.keycode_to_spec(&key_code).and_then(|x| x.chars().next()) {
-    		Some('C') => commit = true,
-    		Some('N') => {
-                if self.index+1<(self.relative_entries.len()) {
+    	match spec {
+            Some(Spec::CommitRaw) => {
+                if !self.key_sequence.is_empty() {
+                    let raw = self.raw();
+                    return self.commit(raw)
+                }
+            },
+
+            // outside of a composition, commit the previous text again or the configured iteration mark
+            Some(Spec::Repeat) => {
+                if self.key_sequence.is_empty() {
+                    match self.config.repeat_mark.clone() {
+                        Some(mark) => return AnkraResponse::Commit(mark),
+                        None if !self.previous_character.is_empty() => {
+                            return AnkraResponse::Commit(self.previous_character.clone())
+                        },
+
+                        None => {}
+                    }
+                }
+            },
+
+    		Some(Spec::Commit) => commit = true,
+            Some(Spec::Insert(text)) => {
+                if self.key_sequence.is_empty() {
+                    return self.commit(text)
+                }
+
+                return match self.respond(true) {
+                    AnkraResponse::Commit(value) => self.commit(value + &text),
+                    response @ AnkraResponse::Candidates(_) => response,
+                    _ => self.commit(text),
+                }
+            },
+
+    		Some(Spec::Next) => {
+                if self.config.navigation==Navigation::Wrap && self.index+1==self.page_end() {
+                    self.index = self.page_start();
+                } else if self.index+1<(self.relative_entries.len()) {
                     self.index += 1;
                 }
             },
 
-    		Some('P') => {
-                if self.index!=0 { 
+            Some(Spec::PageUp) => {
+                self.index = self.page_start().saturating_sub(self.config.page_size());
+            },
+
+            Some(Spec::PageDown) => {
+                if self.page_end()<self.relative_entries.len() {
+                    self.index = self.page_end();
+                }
+            },
+
+    		Some(Spec::Prev) => {
+                if self.config.navigation==Navigation::Wrap && self.index==self.page_start() {
+                    self.index = self.page_end().saturating_sub(1);
+                } else if self.index!=0 { 
                     self.index -= 1;
                 }
             }
 
             // Escape is only considered a key when in input mode
-            Some('E') => {
+            Some(Spec::Escape) => {
                 if !self.key_sequence.is_empty() {
                     self.reset();
                     return AnkraResponse::Empty
                 }
             },
 
-            Some('B') => { 
-                self.key_sequence.pop();
-                self.relative_entries.clear(); 
+            Some(Spec::Backspace) => {
+                if self.key_sequence.pop().is_some() {
+                    self.relative_entries = self.history.pop().unwrap_or_default();
+                    self.index = 0;
+
+                    if self.key_sequence.is_empty() {
+                        self.reset();
+                        return AnkraResponse::Empty
+                    }
+                }
             },
     		
-            Some(x @ '0'..='9') => {
-                self.index = (x as usize)-49; //hacky af conversion
-            }
+            Some(Spec::Wildcard) => {
+                self.key_sequence.push(WILDCARD);
+                self.narrow();
+            },
+
+            Some(Spec::WildcardAny) => {
+                self.key_sequence.push(WILDCARD_ANY);
+                self.narrow();
+            },
 
-            _ => {
-                if let Some(c) = self.config.keycode_to_char(&key_code) {
-                    self.key_sequence.push(*c);
+            Some(Spec::Select(n)) => {
+                let offset = n.saturating_sub(1);
+                if n!=0 && offset<self.config.page_size() && self.page_start()+offset<self.relative_entries.len() {
+                    self.index = self.page_start()+offset;
                 }
-            }
-    	}
+            },
 
-        // get value from dict.csv
-        let result = {
-            if self.relative_entries.is_empty() {
-                for entry in &self.table.entries {
-                    if entry.sequence.starts_with(&self.key_sequence) {
-                        self.relative_entries.push(entry.clone());
+            Some(Spec::ToggleConversion) => {
+                self.converting = !self.converting;
+                if self.key_sequence.is_empty() {
+                    return AnkraResponse::Empty
+                }
+            },
+
+            Some(Spec::ToggleCharset) => {
+                self.filtering = !self.filtering;
+                if self.key_sequence.is_empty() {
+                    return AnkraResponse::Empty
+                }
+
+                self.renarrow();
+            },
+
+            Some(Spec::ReverseLookup) => {
+                if self.key_sequence.is_empty() && self.reverse_lookup.is_some() {
+                    self.looking_up = true;
+                    return AnkraResponse::Empty
+                }
+            },
+
+            // handled above, or by the engine which knows of the other layouts
+            Some(Spec::ToggleAscii | Spec::SwitchLayout(_) | Spec::CycleLayout) => {},
+
+            None => {
+                if let Some(c) = self.config.keycode_to_char(&key_code, self.level).copied() {
+                    if self.config.chord {
+                        return self.press_chord(key_code, c)
+                    }
+
+                    if self.config.auto_select && self.ends_code(c) {
+                        return self.commit_and_continue(c)
                     }
+
+                    self.key_sequence.push(c);
+                    self.narrow();
+                    commit = self.auto_commit();
                 }
-            } else {          
-                self.relative_entries.retain(|entry| entry.sequence.starts_with(&self.key_sequence));
             }
+    	}
 
-            if let Some(entry) = self.relative_entries.get(self.index).map(|x| x.to_owned()) {
-                Some(entry.character.to_string())
-            } else {
+        self.respond(commit)
+    }
+
+    // the candidates for the current key_sequence, or what was committed when `commit` is set
+    fn respond(&mut self, commit: bool) -> AnkraResponse {
+        // keys outside of a composition are not ours to handle
+        if self.key_sequence.is_empty() {
+            self.reset();
+            return AnkraResponse::Undefined
+        }
+
+        // get value from dict.csv
+        self.sentence.clear();
+        if self.relative_entries.is_empty() && self.config.segmentation {
+            self.sentence = segment(&self.table, &self.key_sequence, |candidates, code| self.rank(candidates, code));
+        }
+
+        let result = {
+            if let Some(entry) = self.relative_entries.get(self.index).map(|i| &self.table.entries[*i]) {
+                Some(entry.character.clone())
+            } else if !self.sentence.is_empty() {
+                Some(self.sentence.iter().map(|s| &*self.table.entries[s.entry].character).collect())
+            } else if self.config.no_candidate==NoCandidate::Discard && !self.uncommon_ahead() {
                 self.reset();
                 return AnkraResponse::Empty
+            } else {
+                None
             }
         };
 
         // interpret value from dict.csv
-        if let Some(value) = result {
-            if !self.key_sequence.is_empty() {
-                if commit {
-                    self.reset();
-                    return AnkraResponse::Commit(value)
-                } else {
-                    return AnkraResponse::Suggest(value)
+        match result {
+            Some(value) if commit => {
+                self.learn(&value);
+                self.commit(value)
+            },
+
+            None if commit && self.config.no_candidate==NoCandidate::Raw => {
+                let raw = self.raw();
+                self.commit(raw)
+            },
+
+            _ => AnkraResponse::Candidates(self.candidates())
+        }
+    }
+
+    fn press_lookup(&mut self, key_code: KeyCode) -> AnkraResponse {
+        let response = match self.reverse_lookup.as_mut() {
+            Some(lookup) => {
+                lookup.level = self.level;
+                lookup.on_key_press(key_code)
+            },
+
+            None => AnkraResponse::Undefined
+        };
+
+        self.lookup_response(response)
+    }
+
+    // candidates of the reverse lookup come with their code in this layout, committing leaves it
+    fn lookup_response(&mut self, response: AnkraResponse) -> AnkraResponse {
+        match response {
+            AnkraResponse::Candidates(mut c) => {
+                for candidate in c.candidates.iter_mut() {
+                    candidate.annotation = self.table.sequences(&candidate.text).first().map(|s| s.to_string()).unwrap_or_default();
+                }
+
+                AnkraResponse::Candidates(c)
+            },
+
+            AnkraResponse::Continue(text, response) => {
+                let response = self.lookup_response(*response);
+                self.previous_character = text.clone();
+                AnkraResponse::Continue(text, Box::new(response))
+            },
+
+            AnkraResponse::Commit(text) => {
+                self.looking_up = false;
+                self.commit(text)
+            },
+
+            // eg. escaped or backspaced out of
+            response => {
+                if matches!(&self.reverse_lookup, Some(lookup) if lookup.key_sequence.is_empty()) {
+                    self.looking_up = false;
                 }
+
+                response
             }
         }
+    }
+
+    // whether `key` can't extend a key_sequence which has candidates, see `TableConfig::auto_select`
+    fn ends_code(&self, key: char) -> bool {
+        let mut extended = self.key_sequence.clone();
+        extended.push(key);
+
+        !self.relative_entries.is_empty()
+            && !self.key_sequence.contains([WILDCARD, WILDCARD_ANY])
+            && !self.table.has_prefix(&extended)
+    }
 
+    // commit the selected candidate and start a new key_sequence with `key`
+    fn commit_and_continue(&mut self, key: char) -> AnkraResponse {
+        let committed = match self.respond(true) {
+            AnkraResponse::Commit(text) => text,
+            response => return response,
+        };
+
+        self.key_sequence.push(key);
+        self.narrow();
+        let commit = self.auto_commit();
+
+        match self.respond(commit) {
+            AnkraResponse::Commit(text) => self.commit(committed + &text),
+            response @ AnkraResponse::Candidates(_) => AnkraResponse::Continue(committed, Box::new(response)),
+            // `key` starts no code, it is left to the client right after the commit
+            _ => AnkraResponse::Continue(committed, Box::new(AnkraResponse::Undefined)),
+        }
+    }
+
+    // a key pressed after the chord window starts a new chord, any pending composition gets committed first
+    fn press_chord(&mut self, key_code: KeyCode, key: char) -> AnkraResponse {
+        // repeated presses of a held key
+        if self.chord.is_held(key_code) {
+            return AnkraResponse::Empty
+        }
+
+        let mut response = AnkraResponse::Empty;
+        if self.chord.is_late(Duration::from_millis(self.config.chord_window)) {
+            self.complete_chord();
+        }
+
+        if self.chord.is_empty() && !self.key_sequence.is_empty() {
+            response = self.respond(true);
+        }
+
+        self.chord.press(key_code, key);
+        response
+    }
+
+    // replace the composition with the chord's code
+    fn complete_chord(&mut self) {
+        let code = self.chord.take();
+        self.index = 0;
+        self.relative_entries.clear();
+        self.history.clear();
+        self.key_sequence = code;
+        self.narrow();
+    }
+
+    fn commit(&mut self, value: String) -> AnkraResponse {
         self.reset();
-        AnkraResponse::Undefined
+        self.previous_character = value.clone();
+        AnkraResponse::Commit(value)
+    }
+
+    // committed text as sent to the client, converted once by the engine whatever built it
+    pub fn converted(&self, response: AnkraResponse) -> AnkraResponse {
+        match response {
+            AnkraResponse::Commit(text) => AnkraResponse::Commit(self.convert(&text)),
+            AnkraResponse::Continue(text, response) => AnkraResponse::Continue(self.convert(&text), response),
+            response => response
+        }
+    }
+
+    pub fn convert(&self, text: &str) -> String {
+        if self.converting && !self.converter.is_empty() {
+            self.converter.convert(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    // a candidate as shown, see `TableConfig::convert_candidates`
+    fn display(&self, text: &str) -> String {
+        if self.config.convert_candidates {
+            self.convert(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    // the key_sequence as typed, keys given a label in the config are replaced by it
+    pub fn raw(&self) -> String {
+        self.key_sequence.chars()
+            .map(|c| self.config.labels.get(&c).cloned().unwrap_or_else(|| c.to_string()))
+            .collect()
+    }
+
+    // the page of candidates holding the selected one
+    pub fn candidates(&self) -> Candidates {
+        let key_sequence = self.key_sequence.clone();
+        if !self.sentence.is_empty() {
+            let text = self.display(&self.sentence.iter().map(|s| &*self.table.entries[s.entry].character).collect::<String>());
+            return Candidates {
+                preedit: self.preedit(&text, 1),
+                candidates: vec![Candidate { text, label: String::from("1"), remaining: String::new(), annotation: String::new() }],
+                selected: 0,
+                total: 1,
+                key_sequence,
+            }
+        }
+
+        let start = self.page_start();
+        let candidates = self.relative_entries[start..self.page_end()].iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let entry = &self.table.entries[*e];
+                Candidate {
+                    text: self.display(&entry.character),
+                    label: label(i),
+                    remaining: remaining(&entry.sequence, &self.key_sequence),
+                    annotation: String::new(),
+                }
+            })
+            .collect::<Vec<Candidate>>();
+
+        // without any candidate, what was typed stands in for one
+        let text = candidates.get(self.index-start).map(|c| c.text.clone()).unwrap_or_else(|| self.raw());
+        Candidates {
+            preedit: self.preedit(&text, self.relative_entries.len()),
+            candidates,
+            selected: self.index-start,
+            total: self.relative_entries.len(),
+            key_sequence,
+        }
+    }
+
+    // fill in the layout's preedit template, the cursor covers the candidate unless placed with {cursor}
+    fn preedit(&self, candidate: &str, total: usize) -> Preedit {
+        let mut preedit = Preedit::default();
+        let mut caret = None;
+        let mut highlight = None;
+
+        let mut rest = &*self.config.preedit;
+        while !rest.is_empty() {
+            let (literal, field, tail) = match rest.split_once('{').and_then(|(l, t)| t.split_once('}').map(|(f, t)| (l, f, t))) {
+                Some(parts) => parts,
+                None => (rest, "", ""),
+            };
+
+            preedit.push(PreeditSegment::Text(literal.to_string()));
+            match field {
+                "" => {},
+                "keys" => preedit.push(PreeditSegment::Keys(self.key_sequence.clone())),
+                "position" => preedit.push(PreeditSegment::Position(format!("({}/{})", self.index+1, total))),
+                "cursor" => caret = Some(preedit.len()),
+                "candidate" => {
+                    let start = preedit.len();
+                    preedit.push(PreeditSegment::Candidate(candidate.to_string()));
+                    highlight = Some((start, preedit.len()));
+                },
+
+                field => preedit.push(PreeditSegment::Text(format!("{{{}}}", field))),
+            }
+
+            rest = tail;
+        }
+
+        preedit.cursor = caret.map(|c| (c, c)).or(highlight);
+        preedit
+    }
+
+    fn page_start(&self) -> usize {
+        self.index / self.config.page_size() * self.config.page_size()
+    }
+
+    fn page_end(&self) -> usize {
+        (self.page_start()+self.config.page_size()).min(self.relative_entries.len())
+    }
+
+    // push the entries matching the current key_sequence on top of the history
+    fn narrow(&mut self) {
+        let mut narrowed = if self.key_sequence.contains([WILDCARD, WILDCARD_ANY]) {
+            self.table.index.matching(&self.key_sequence, WILDCARD, WILDCARD_ANY)
+        } else if self.history.is_empty() && self.relative_entries.is_empty() {
+            self.table.lookup(&self.key_sequence)
+        } else {
+            self.relative_entries.iter()
+                .copied()
+                .filter(|i| self.table.entries[*i].sequence.starts_with(&self.key_sequence))
+                .collect()
+        };
+
+        if self.filtering && !self.charset.is_empty() {
+            narrowed.retain(|i| self.charset.contains(&self.table.entries[*i].character));
+
+            // uncommon characters whose whole code was typed
+            let exact = self.table.exact(&self.key_sequence);
+            if self.config.uncommon_by_code && !exact.is_empty() {
+                narrowed.extend_from_slice(exact);
+            }
+        }
+
+        // back to table order, else the ranking of the previous key_sequence breaks ties
+        narrowed.sort_unstable();
+        narrowed.dedup();
+        self.rank(&mut narrowed, &self.key_sequence);
+        self.history.push(std::mem::replace(&mut self.relative_entries, narrowed));
+        self.index = 0;
+    }
+
+    // narrow the key_sequence again from the start, eg. once the filter changed
+    fn renarrow(&mut self) {
+        let keys = std::mem::take(&mut self.key_sequence);
+        self.relative_entries.clear();
+        self.history.clear();
+
+        for c in keys.chars() {
+            self.key_sequence.push(c);
+            self.narrow();
+        }
+    }
+
+    // every candidate hidden by charsets, though typing on may still reach an uncommon character by its whole code
+    fn uncommon_ahead(&self) -> bool {
+        self.filtering && self.config.uncommon_by_code && !self.charset.is_empty() && self.table.has_prefix(&self.key_sequence)
+    }
+
+    // a single entry left whose code is complete, or as many keys typed as codes can be long
+    fn auto_commit(&self) -> bool {
+        let unique = match &*self.relative_entries {
+            [i] => self.table.entries[*i].sequence==self.key_sequence && !self.table.extends(&self.key_sequence),
+            _ => false,
+        };
+
+        let full = matches!(self.config.max_length, Some(max) if self.key_sequence.chars().count()>=max);
+        (self.config.auto_commit && unique) || full
+    }
+
+    fn rank(&self, indices: &mut [usize], key_sequence: &str) {
+        self.config.ranking.sort(indices, &self.table, key_sequence);
+        self.dict.sort(indices, &self.table, key_sequence);
+    }
+
+    // remember what was committed for the current key_sequence, piece by piece for a sentence
+    fn learn(&mut self, value: &str) {
+        if self.sentence.is_empty() {
+            self.dict.learn(&self.key_sequence, value);
+        } else {
+            for s in &self.sentence {
+                self.dict.learn(&s.code, &self.table.entries[s.entry].character);
+            }
+        }
+    }
+
+    // write what was learnt, kept off the key handling path, see `AnkraEngine::reset`
+    pub fn save(&mut self) {
+        if let Err(e) = self.dict.save() {
+            log::warn!("failed to save user dictionary: {}", e);
+        }
     }
 
-    pub fn on_key_release(&mut self, _key_code: u16) -> AnkraResponse {
-        AnkraResponse::Undefined
+    pub fn on_key_release(&mut self, key_code: u16) -> AnkraResponse {
+        if self.looking_up {
+            let response = match self.reverse_lookup.as_mut() {
+                Some(lookup) => lookup.on_key_release(key_code),
+                None => AnkraResponse::Undefined,
+            };
+
+            return self.lookup_response(response)
+        }
+
+        if !self.chord.release(key_code) {
+            return AnkraResponse::Undefined
+        }
+
+        if self.chord.is_released() && !self.chord.is_empty() {
+            self.complete_chord();
+            let commit = self.auto_commit();
+            return self.respond(commit)
+        }
+
+        AnkraResponse::Empty
+    }
+
+    // what is being composed, left as it is
+    pub fn current(&mut self) -> AnkraResponse {
+        self.respond(false)
+    }
+
+    // what was being composed is committed or dropped as configured, see `TableConfig::ascii_toggle`
+    pub fn finish(&mut self) -> AnkraResponse {
+        if self.key_sequence.is_empty() {
+            return AnkraResponse::Empty
+        }
+
+        match self.config.ascii_toggle {
+            AsciiToggle::Commit => self.respond(true),
+            AsciiToggle::CommitRaw => {
+                let raw = self.raw();
+                self.commit(raw)
+            },
+
+            AsciiToggle::Discard => {
+                self.reset();
+                AnkraResponse::Empty
+            }
+        }
     }
 
     pub fn reset(&mut self) {
+        if let Some(lookup) = self.reverse_lookup.as_mut() {
+            lookup.reset();
+        }
+
+        self.looking_up = false;
         self.index = 0;
         self.relative_entries.clear();
+        self.history.clear();
+        self.sentence.clear();
+        self.chord.clear();
         self.key_sequence.clear();
-        self.previous_character.clear();
+    }
+}
+
+// digit key selecting the candidate at `offset` in a page
+fn label(offset: usize) -> String {
+    match offset {
+        0..=8 => (offset+1).to_string(),
+        9 => String::from("0"),
+        _ => String::new(),
+    }
+}
+
+// keys still to be typed to reach `sequence`, the whole sequence when a `WILDCARD_ANY` makes that unclear
+fn remaining(sequence: &str, key_sequence: &str) -> String {
+    if key_sequence.contains(WILDCARD_ANY) {
+        sequence.to_string()
+    } else {
+        sequence.chars().skip(key_sequence.chars().count()).collect()
     }
 }
 
 #[derive(Default, Debug, Deserialize)]
 pub struct Table {
     pub id: String,
-    pub entries: Vec<Entry>
+    pub entries: Vec<Entry>,
+    #[serde(skip)]
+    pub index: Trie,
+    // entries producing a given text, for looking up how to type it
+    #[serde(skip)]
+    pub reverse: HashMap<String, Vec<usize>>,
 }
 
 #[derive(Default, Debug, Clone, Deserialize)]
 pub struct Entry {
-    pub character: char,
+    // a single character, a phrase or any other grapheme cluster
+    pub character: String,
     pub sequence: String, //maybe try a tiny_string as this is needlessly large
+    #[serde(alias = "frequency")]
+    pub weight: Option<u32>,
 }
 
 impl Table {
     pub fn from_path(id: &str, base_dir: &Path) -> Result<Self, AnkraError> {
         let path = base_dir.join(id).join("table").with_extension("csv");
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let entries = csv::Reader::from_reader(reader).deserialize().collect::<Result<Vec<_>, _>>()?;
+        let entries = Self::read(&path).map_err(|e| e.in_file(&path))?;
+
+        let mut index = Trie::default();
+        let mut reverse: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            index.insert(&entry.sequence, i);
+            reverse.entry(entry.character.clone()).or_default().push(i);
+        }
 
         Ok(Self {
             id: id.to_string(),
             entries,
+            index,
+            reverse,
         })
     }
+
+    fn read(path: &Path) -> Result<Vec<Entry>, AnkraError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(csv::Reader::from_reader(reader).deserialize().collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // rewrite every sequence, eg. so that chords typed in any order match
+    pub fn normalise(&mut self, f: fn(&str) -> String) {
+        self.index = Trie::default();
+        for (i, entry) in self.entries.iter_mut().enumerate() {
+            entry.sequence = f(&entry.sequence);
+            self.index.insert(&entry.sequence, i);
+        }
+    }
+
+    // indices of every entry whose sequence starts with `prefix`, in table order
+    pub fn lookup(&self, prefix: &str) -> Vec<usize> {
+        self.index.prefixed(prefix)
+    }
+
+    // indices of the entries whose sequence is exactly `sequence`
+    pub fn exact(&self, sequence: &str) -> &[usize] {
+        self.index.get(sequence)
+    }
+
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        self.index.contains_prefix(prefix)
+    }
+
+    // whether a longer sequence starts with `prefix`, whatever charsets hide
+    pub fn extends(&self, prefix: &str) -> bool {
+        self.index.extends(prefix)
+    }
+
+    // sequences producing `text`, shortest first
+    pub fn sequences(&self, text: &str) -> Vec<&str> {
+        let mut sequences: Vec<&str> = self.reverse.get(text)
+            .into_iter()
+            .flatten()
+            .map(|i| &*self.entries[*i].sequence)
+            .collect();
+
+        sequences.sort_by_key(|s| s.chars().count());
+        sequences
+    }
 }
 
 #[derive(Default, Debug, Deserialize)]
 pub struct TableConfig {
-    pub specs: HashMap<KeyCode, Vec<String>>,
+    pub specs: HashMap<KeyCode, Vec<Spec>>,
     pub keys: HashMap<KeyCode, Vec<char>>,
+    #[serde(default)]
+    pub ranking: Ranking,
+    // split a run of keys no single code starts with into several codes, eg. hqidup => 我想
+    #[serde(default)]
+    pub segmentation: bool,
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+    #[serde(default)]
+    pub navigation: Navigation,
+    // made of {keys}, {candidate}, {position} and {cursor} amongst plain text
+    #[serde(default = "default_preedit")]
+    pub preedit: String,
+    #[serde(default)]
+    pub no_candidate: NoCandidate,
+    // text standing in for a key when the key_sequence is shown or committed as typed
+    #[serde(default)]
+    pub labels: HashMap<char, String>,
+    // committed by REPEAT instead of the previous text, eg. 々
+    #[serde(default)]
+    pub repeat_mark: Option<String>,
+    // keys pressed together form one code, looked up once they are all released
+    #[serde(default)]
+    pub chord: bool,
+    // milliseconds after a chord's first key within which further keys still join it
+    #[serde(default = "default_chord_window")]
+    pub chord_window: u64,
+    #[serde(default)]
+    pub ascii_toggle: AsciiToggle,
+    // commit as soon as the key_sequence matches a single entry with nothing longer starting with it
+    #[serde(default)]
+    pub auto_commit: bool,
+    // commit once the key_sequence is this many keys long, eg. 5 for cangjie
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    // commit the selected candidate when a key can't extend the key_sequence, the key then starts a new one
+    #[serde(default)]
+    pub auto_select: bool,
+    // layout whose codes for committed text are given as a hint, eg. cangjie5 when typing through a phonetic layout
+    #[serde(default)]
+    pub hint_layout: Option<String>,
+    // layout typed through after REVERSE_LOOKUP, its candidates annotated with their code in this one, eg. pinyin
+    #[serde(default)]
+    pub reverse_lookup: Option<String>,
+    // OpenCC dictionaries converting committed text, relative to the layout's folder, eg. ["TSPhrases.txt", "TSCharacters.txt"]
+    #[serde(default)]
+    pub conversion: Vec<String>,
+    // convert candidates as they are shown as well
+    #[serde(default)]
+    pub convert_candidates: bool,
+    // sets of common characters candidates are limited to, all of them are listed when empty
+    #[serde(default)]
+    pub charsets: Vec<charset::Set>,
+    // characters outside of `charsets` are still listed once their whole code is typed
+    #[serde(default)]
+    pub uncommon_by_code: bool,
+}
+
+fn default_chord_window() -> u64 {
+    100
+}
+
+// what happens to a key_sequence nothing matches
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoCandidate {
+    // drop it as soon as it stops matching
+    #[default]
+    Discard,
+    // keep composing, COMMIT commits it as typed
+    Raw,
+    // keep composing, COMMIT does nothing
+    Compose,
+}
+
+// what happens to the key_sequence when TOGGLE_ASCII is tapped while composing
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AsciiToggle {
+    // commit the selected candidate
+    Commit,
+    // commit it as typed
+    #[default]
+    CommitRaw,
+    Discard,
+}
+
+fn default_page_size() -> usize {
+    9
+}
+
+fn default_preedit() -> String {
+    String::from("{candidate}")
+}
+
+// what NEXT and PREV do at the edges of a page
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Navigation {
+    // move on to the neighbouring page
+    #[default]
+    Cross,
+    // go round to the other end of the same page
+    Wrap,
+}
+
+#[derive(Default, Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ranking {
+    #[default]
+    TableOrder,
+    Weight,
+    ExactFirst,
+    ShortestFirst,
+    Codepoint,
+}
+
+impl Ranking {
+    // stable, so entries ranked equal keep their table order
+    pub fn sort(&self, indices: &mut [usize], table: &Table, key_sequence: &str) {
+        let entry = |i: &usize| &table.entries[*i];
+        match self {
+            Ranking::TableOrder => {},
+            Ranking::Weight => indices.sort_by_key(|i| std::cmp::Reverse(entry(i).weight.unwrap_or(0))),
+            Ranking::ExactFirst => indices.sort_by_key(|i| entry(i).sequence != key_sequence),
+            Ranking::ShortestFirst => indices.sort_by_key(|i| entry(i).sequence.len()),
+            Ranking::Codepoint => indices.sort_by(|a, b| entry(a).character.cmp(&entry(b).character)),
+        }
+    }
 }
 
 impl TableConfig {
     pub fn from_path(id: &str, base_dir: &Path) -> Result<Self, AnkraError> {
         let path = base_dir.join(id).join("config").with_extension("zm");
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        Ok(zmerald::from_reader(reader).unwrap())
+        Self::read(&path).map_err(|e| e.in_file(&path))
+    }
+
+    fn read(path: &Path) -> Result<Self, AnkraError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(zmerald::from_reader(reader)?)
     }
 
-    pub fn keycode_to_char(&self, keycode: &KeyCode) -> Option<&char> {
-        self.keys.get(keycode)?.first()
+    pub fn page_size(&self) -> usize {
+        self.page_size.max(1)
     }
 
-    pub fn keycode_to_spec(&self, keycode: &KeyCode) -> Option<&str> {
-        self.specs.get(keycode)?.first().map(|x| &**x)
+    // no fallback, eg. Shift+a is left to the client as a capital unless bound
+    pub fn keycode_to_char(&self, keycode: &KeyCode, level: usize) -> Option<&char> {
+        self.keys.get(keycode)?.get(level)
     }
-}
\ No newline at end of file
+
+    // levels left unbound fall back to the base spec, eg. Shift+Space still commits
+    pub fn keycode_to_spec(&self, keycode: &KeyCode, level: usize) -> Option<&Spec> {
+        let specs = self.specs.get(keycode)?;
+        specs.get(level).or_else(|| specs.first())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(rows: &[(&str, &str)]) -> TableState {
+        let mut table = Table::default();
+        for (i, (character, sequence)) in rows.iter().enumerate() {
+            table.entries.push(Entry { character: character.to_string(), sequence: sequence.to_string(), weight: None });
+            table.index.insert(sequence, i);
+        }
+
+        let config = TableConfig {
+            specs: HashMap::from([(22, vec![Spec::Backspace])]),
+            keys: HashMap::from([(38, vec!['a']), (39, vec!['s'])]),
+            ..Default::default()
+        };
+
+        TableState { table, config, ..Default::default() }
+    }
+
+    // only the first key walks the trie, further keys filter what it found and backspace pops it back
+    #[test]
+    fn narrow_without_rescan() {
+        let mut state = state(&[("日", "a"), ("曰", "a"), ("昌", "aa"), ("明", "as"), ("月", "s"), ("晶", "asa")]);
+        state.on_key_press(38);
+        assert_eq!(state.relative_entries, [0, 1, 2, 3, 5]);
+
+        // anything still reaching for the table would find nothing
+        state.table.index = Trie::default();
+        state.on_key_press(39);
+        assert_eq!(state.relative_entries, [3, 5]);
+        state.on_key_press(38);
+        assert_eq!(state.relative_entries, [5]);
+        assert_eq!(state.history, [vec![], vec![0, 1, 2, 3, 5], vec![3, 5]]);
+
+        state.on_key_press(22);
+        assert_eq!(state.relative_entries, [3, 5]);
+        state.on_key_press(22);
+        assert_eq!(state.relative_entries, [0, 1, 2, 3, 5]);
+        assert_eq!(state.history, [Vec::<usize>::new()]);
+    }
+}
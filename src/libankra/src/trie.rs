@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+// prefix index over table sequences, every node keeps the entries whose sequence ends there
+#[derive(Debug)]
+pub struct Trie {
+    nodes: Vec<Node>,
+}
+
+#[derive(Default, Debug)]
+struct Node {
+    children: Vec<(char, usize)>,
+    values: Vec<usize>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Trie { nodes: vec![Node::default()] }
+    }
+}
+
+impl Trie {
+    pub fn insert(&mut self, key: &str, value: usize) {
+        let mut node = 0;
+        for c in key.chars() {
+            node = match self.nodes[node].children.binary_search_by_key(&c, |(k, _)| *k) {
+                Ok(i) => self.nodes[node].children[i].1,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.insert(i, (c, child));
+                    child
+                }
+            };
+        }
+
+        self.nodes[node].values.push(value);
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        let mut node = 0;
+        for c in key.chars() {
+            let children = &self.nodes[node].children;
+            node = children.binary_search_by_key(&c, |(k, _)| *k).ok().map(|i| children[i].1)?;
+        }
+
+        Some(node)
+    }
+
+    // values stored under exactly `key`
+    pub fn get(&self, key: &str) -> &[usize] {
+        self.find(key).map(|node| &*self.nodes[node].values).unwrap_or(&[])
+    }
+
+    pub fn contains_prefix(&self, prefix: &str) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    // whether some key is longer than `prefix` and starts with it
+    pub fn extends(&self, prefix: &str) -> bool {
+        matches!(self.find(prefix), Some(node) if !self.nodes[node].children.is_empty())
+    }
+
+    // values of every key starting with `prefix`, in insertion order
+    pub fn prefixed(&self, prefix: &str) -> Vec<usize> {
+        let mut values = Vec::new();
+        if let Some(node) = self.find(prefix) {
+            self.collect(node, &mut values);
+        }
+
+        values.sort_unstable();
+        values
+    }
+
+    // like `prefixed`, where `one` in the pattern stands for any single key and `any` for any run of keys
+    pub fn matching(&self, pattern: &str, one: char, any: char) -> Vec<usize> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut seen = HashSet::new();
+        let mut values = Vec::new();
+        let mut stack = vec![(0, 0)];
+
+        while let Some((node, pos)) = stack.pop() {
+            if !seen.insert((node, pos)) {
+                continue
+            }
+
+            let children = &self.nodes[node].children;
+            match pattern.get(pos) {
+                None => self.collect(node, &mut values),
+                Some(c) if *c==any => {
+                    stack.push((node, pos+1));
+                    stack.extend(children.iter().map(|(_, child)| (*child, pos)));
+                },
+
+                Some(c) if *c==one => stack.extend(children.iter().map(|(_, child)| (*child, pos+1))),
+                Some(c) => {
+                    if let Ok(i) = children.binary_search_by_key(c, |(k, _)| *k) {
+                        stack.push((children[i].1, pos+1));
+                    }
+                }
+            }
+        }
+
+        values.sort_unstable();
+        values.dedup();
+        values
+    }
+
+    fn collect(&self, node: usize, values: &mut Vec<usize>) {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            values.extend_from_slice(&self.nodes[node].values);
+            stack.extend(self.nodes[node].children.iter().map(|(_, child)| *child));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // keys of one to five letters out of a few, so that prefixes are shared a lot
+    fn keys() -> Vec<String> {
+        let mut seed: u32 = 7;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as usize
+        };
+
+        (0..2000).map(|_| (0..1+next()%5).map(|_| b"abcde"[next()%5] as char).collect()).collect()
+    }
+
+    fn prefixes() -> Vec<String> {
+        let mut prefixes = vec![String::new()];
+        for _ in 0..3 {
+            let longer: Vec<String> = prefixes.iter().flat_map(|p| "abcdef".chars().map(move |c| format!("{}{}", p, c))).collect();
+            prefixes.extend(longer);
+        }
+
+        prefixes
+    }
+
+    #[test]
+    fn linear_scan() {
+        let keys = keys();
+        let mut trie = Trie::default();
+        for (i, key) in keys.iter().enumerate() {
+            trie.insert(key, i);
+        }
+
+        for prefix in prefixes() {
+            let prefixed: Vec<usize> = (0..keys.len()).filter(|i| keys[*i].starts_with(&prefix)).collect();
+            let exact: Vec<usize> = (0..keys.len()).filter(|i| keys[*i]==prefix).collect();
+
+            assert_eq!(trie.prefixed(&prefix), prefixed, "{}", prefix);
+            assert_eq!(trie.get(&prefix), &exact[..], "{}", prefix);
+            assert_eq!(trie.contains_prefix(&prefix), !prefixed.is_empty(), "{}", prefix);
+            assert_eq!(trie.extends(&prefix), prefixed.len() > exact.len(), "{}", prefix);
+        }
+    }
+}
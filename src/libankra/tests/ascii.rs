@@ -0,0 +1,23 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout };
+
+#[test]
+fn ascii_layout_keys() {
+    let mut engine = layout("ascii_layout_keys", "", "character,sequence\n日,a\n");
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Suggest(String::from("日")));
+    assert_eq!(engine.on_key_press(50), AnkraResponse::Suggest(String::from("日")));
+    assert_eq!(engine.on_key_release(50), AnkraResponse::Commit(String::from("a")));
+
+    // CYCLE_LAYOUT is typed through as well
+    engine.set_level(1);
+    assert_eq!(engine.on_key_press(49), AnkraResponse::Undefined);
+    engine.set_level(0);
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Undefined);
+
+    assert_eq!(engine.on_key_press(50), AnkraResponse::Undefined);
+    assert_eq!(engine.on_key_release(50), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Suggest(String::from("日")));
+}
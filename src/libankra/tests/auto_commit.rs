@@ -0,0 +1,38 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout, test_input_impl };
+
+const TABLE: &str = "character,sequence\n日,a\n曰,a\n昌,aa\n明,ab\n月,b\n朋,bb\n";
+
+#[test]
+fn auto_commit() {
+    test_input_impl(layout("auto_commit", "auto_commit: true", TABLE), &[
+        // two entries coded a, and longer codes starting with it
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('b'), AnkraResponse::Commit(String::from("明"))),
+        // b is a whole code, yet bb may follow
+        (code('b'), AnkraResponse::Suggest(String::from("月"))),
+        (code('b'), AnkraResponse::Commit(String::from("朋"))),
+    ])
+}
+
+#[test]
+fn auto_commit_off() {
+    test_input_impl(layout("auto_commit_off", "", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('b'), AnkraResponse::Suggest(String::from("明"))),
+        (65, AnkraResponse::Commit(String::from("明"))),
+    ])
+}
+
+#[test]
+fn max_length() {
+    test_input_impl(layout("max_length", "max_length: Some(2)", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('a'), AnkraResponse::Commit(String::from("昌"))),
+        (code('b'), AnkraResponse::Suggest(String::from("月"))),
+        (code('b'), AnkraResponse::Commit(String::from("朋"))),
+    ])
+}
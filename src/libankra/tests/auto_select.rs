@@ -0,0 +1,58 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, layout, test_input_impl };
+
+const TABLE: &str = "character,sequence\n日,a\n明,ab\n月,b\n";
+
+#[test]
+fn auto_select() {
+    test_input_impl(layout("auto_select", "auto_select: true", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('b'), AnkraResponse::Suggest(String::from("明"))),
+        (code('b'), AnkraResponse::Continue(String::from("明"), Box::new(AnkraResponse::Suggest(String::from("月"))))),
+        (65, AnkraResponse::Commit(String::from("月"))),
+    ])
+}
+
+// the key starting no code is left to the client instead of being dropped
+#[test]
+fn auto_select_no_match() {
+    test_input_impl(layout("auto_select_no_match", "auto_select: true", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('x'), AnkraResponse::Continue(String::from("日"), Box::new(AnkraResponse::Undefined))),
+        (code('b'), AnkraResponse::Suggest(String::from("月"))),
+    ])
+}
+
+#[test]
+fn auto_select_candidates() {
+    layout("auto_select_candidates", "auto_select: true", TABLE);
+    let mut engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("auto_select_candidates"),
+        dir: std::env::temp_dir().join("ankra-tests"),
+        data_dir: None,
+        candidate_list: true,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    engine.on_key_press(code('a'));
+    match engine.on_key_press(code('b')) {
+        AnkraResponse::Candidates(c) => assert_eq!(c.text(), Some("明")),
+        response => panic!("{:?}", response),
+    }
+
+    match engine.on_key_press(code('b')) {
+        AnkraResponse::Continue(committed, response) => match *response {
+            AnkraResponse::Candidates(c) => {
+                assert_eq!(committed, "明");
+                assert_eq!(c.text(), Some("月"));
+            },
+
+            response => panic!("{:?}", response),
+        },
+
+        response => panic!("{:?}", response),
+    }
+}
@@ -0,0 +1,36 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraEngine, AnkraConfig };
+use shared::write_layout;
+use std::path::PathBuf;
+
+#[test]
+fn missing_layout() {
+    let result = AnkraEngine::new(AnkraConfig {
+        id: String::from("missing"),
+        dir: PathBuf::from("/nonexistent"),
+        data_dir: None,
+        ..AnkraConfig::default()
+    });
+
+    match result {
+        Err(e) => assert!(e.to_string().contains("/nonexistent/missing/config.zm"), "{}", e),
+        Ok(_) => panic!("loaded a missing layout"),
+    }
+}
+
+#[test]
+fn empty_id() {
+    let dir = write_layout("empty_id", "{ specs: { 65: [ COMMIT ] }, keys: { 24: [ q ] } }", "character,sequence\n手,q\n");
+
+    let engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("\n"),
+        layouts: vec![String::from("empty_id")],
+        dir,
+        data_dir: None,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    assert_eq!(engine.layout(), "empty_id");
+}
@@ -19,7 +19,17 @@ fn cangjie_next_key() {
         (38, 0, AnkraResponse::Suggest(String::from("日"))),
         (23, 0, AnkraResponse::Suggest(String::from("曰"))), // next
         (23, 1, AnkraResponse::Suggest(String::from("日"))), // prev
-        (65, 0, AnkraResponse::Commit(String::from("曰")))
+        (65, 0, AnkraResponse::Commit(String::from("日")))
+    ])
+}
+
+#[test]
+fn cangjie_unbound_level() {
+    test_input_with_level(&[
+        (38, 1, AnkraResponse::Undefined), // no shifted key, left to the client
+        (38, 0, AnkraResponse::Suggest(String::from("日"))),
+        (23, 2, AnkraResponse::Suggest(String::from("曰"))), // next, only two levels bound
+        (65, 1, AnkraResponse::Commit(String::from("曰"))) // commit, unbound at level 1
     ])
 }
 
@@ -52,4 +62,27 @@ fn cangjie_on_no_result() {
         (24, AnkraResponse::Suggest(String::from("掱"))),
         (65, AnkraResponse::Commit(String::from("掱"))),
     ])
+}
+
+#[test]
+fn cangjie_candidate_list() {
+    let mut engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("cangjie5"),
+        data_dir: None,
+        candidate_list: true,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    match engine.on_key_press(24) {
+        AnkraResponse::Candidates(c) => {
+            assert_eq!(c.text(), Some("手"));
+            assert_eq!(c.key_sequence, "q");
+            assert_eq!(c.selected, 0);
+            assert_eq!(c.candidates[0].label, "1");
+            assert_eq!(c.candidates[0].remaining, "");
+            assert!(c.total >= c.candidates.len());
+        },
+
+        rep => panic!("expected candidates, got {:?}", rep)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout, test_input_impl };
+
+// 䀹 lies outside of the common range
+const TABLE: &str = "character,sequence\n日,a\n曰,a\n䀹,xab\n月,b\n";
+
+#[test]
+fn uncommon_by_code() {
+    test_input_impl(layout("uncommon_by_code", "charsets: [ range(一, 鿿) ], uncommon_by_code: true", TABLE), &[
+        (code('x'), AnkraResponse::Suggest(String::from("x"))),
+        (code('a'), AnkraResponse::Suggest(String::from("xa"))),
+        (code('b'), AnkraResponse::Suggest(String::from("䀹"))),
+        (65, AnkraResponse::Commit(String::from("䀹"))),
+    ])
+}
+
+#[test]
+fn uncommon_hidden() {
+    test_input_impl(layout("uncommon_hidden", "charsets: [ range(一, 鿿) ]", TABLE), &[
+        (code('x'), AnkraResponse::Empty),
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+    ])
+}
+
+// 日 is the only common entry under a, yet typing on reaches 䀹
+#[test]
+fn uncommon_auto_commit() {
+    let options = "charsets: [ range(一, 鿿) ], uncommon_by_code: true, auto_commit: true";
+    test_input_impl(layout("uncommon_auto_commit", options, "character,sequence\n日,a\n䀹,ab\n月,b\n"), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('b'), AnkraResponse::Commit(String::from("䀹"))),
+        (code('b'), AnkraResponse::Commit(String::from("月"))),
+    ])
+}
@@ -0,0 +1,40 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout };
+use std::time::Duration;
+
+const TABLE: &str = "character,sequence\n日,a\n手,q\n明,qa\n";
+
+#[test]
+fn chord_any_order() {
+    let mut engine = layout("chord_any_order", "chord: true, chord_window: 60000", TABLE);
+    assert_eq!(engine.on_key_press(code('q')), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Empty);
+    // looked up once every key is released
+    assert_eq!(engine.on_key_release(code('q')), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_release(code('a')), AnkraResponse::Suggest(String::from("明")));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("明")));
+
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_press(code('q')), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_release(code('a')), AnkraResponse::Empty);
+    assert_eq!(engine.on_key_release(code('q')), AnkraResponse::Suggest(String::from("明")));
+
+    // a new chord commits the previous one
+    assert_eq!(engine.on_key_press(code('q')), AnkraResponse::Commit(String::from("明")));
+    assert_eq!(engine.on_key_release(code('q')), AnkraResponse::Suggest(String::from("手")));
+}
+
+#[test]
+fn chord_late_key() {
+    let mut engine = layout("chord_late_key", "chord: true, chord_window: 0", TABLE);
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Empty);
+    std::thread::sleep(Duration::from_millis(5));
+
+    // past the window, q starts a chord of its own
+    assert_eq!(engine.on_key_press(code('q')), AnkraResponse::Commit(String::from("日")));
+    assert_eq!(engine.on_key_release(code('a')), AnkraResponse::Undefined);
+    assert_eq!(engine.on_key_release(code('q')), AnkraResponse::Suggest(String::from("手")));
+}
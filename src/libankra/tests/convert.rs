@@ -0,0 +1,20 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout, test_input_impl };
+
+// 后 becomes 後, which would become 后 again if converted twice
+#[test]
+fn converted_once() {
+    let dir = std::env::temp_dir().join("ankra-tests").join("converted_once");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("dict.txt"), "后\t後\n後\t后\n").unwrap();
+
+    test_input_impl(layout("converted_once", r#"conversion: [ "dict.txt" ]"#, "character,sequence\n后,a\n"), &[
+        (code('a'), AnkraResponse::Suggest(String::from("后"))),
+        (60, AnkraResponse::Commit(String::from("後。"))),
+        (code('a'), AnkraResponse::Suggest(String::from("后"))),
+        (65, AnkraResponse::Commit(String::from("後"))),
+    ])
+}
@@ -0,0 +1,37 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, config, write_layout };
+
+#[test]
+fn hint() {
+    let dir = write_layout("hint_codes", &config(""), "character,sequence\n後,xyz\n後,xy\n");
+    std::fs::write(dir.join("hint_codes").join("ts.txt"), "後\t后\n").unwrap();
+
+    // typed in a layout which converts to simplified, hinted with the traditional codes
+    let options = r#"hint_layout: Some("hint_codes"), conversion: [ "../hint_codes/ts.txt" ]"#;
+    write_layout("hint", &config(options), "character,sequence\n後,a\n日,b\n");
+    let mut engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("hint"),
+        layouts: vec![String::from("hint"), String::from("hint_codes")],
+        dir,
+        data_dir: None,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    engine.on_key_press(code('a'));
+    assert_eq!(engine.hint(), None);
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("后")));
+
+    let hint = engine.hint().unwrap();
+    assert_eq!(hint.text, "后");
+    assert_eq!(hint.layout, "hint_codes");
+    assert_eq!(hint.sequences, ["xy", "xyz"]);
+
+    // nothing to hint for text the other layout can't type
+    engine.on_key_press(code('b'));
+    assert_eq!(engine.hint(), None);
+    engine.on_key_press(65);
+    assert_eq!(engine.hint(), None);
+}
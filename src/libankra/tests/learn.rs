@@ -0,0 +1,35 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, write_layout };
+
+const CONFIG: &str = "{ specs: { 23: [ NEXT ], 65: [ COMMIT ] }, keys: { 38: [ a ] } }";
+
+fn load(data_dir: &std::path::Path) -> AnkraEngine {
+    AnkraEngine::new(AnkraConfig {
+        id: String::from("learn"),
+        dir: write_layout("learn", CONFIG, "character,sequence\n日,a\n曰,a\n"),
+        data_dir: Some(data_dir.to_path_buf()),
+        ..AnkraConfig::default()
+    }).unwrap()
+}
+
+#[test]
+fn learn() {
+    let data_dir = std::env::temp_dir().join("ankra-tests").join("learn-data");
+    let _ = std::fs::remove_dir_all(&data_dir);
+
+    let mut engine = load(&data_dir);
+    engine.on_key_press(code('a'));
+    engine.on_key_press(23);
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("曰")));
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Suggest(String::from("曰")));
+
+    // saved once the engine goes, not on every commit
+    assert!(!data_dir.join("learn").join("user.csv").exists());
+    drop(engine);
+
+    let mut engine = load(&data_dir);
+    assert_eq!(engine.on_key_press(code('a')), AnkraResponse::Suggest(String::from("曰")));
+}
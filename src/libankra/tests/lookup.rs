@@ -0,0 +1,25 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine };
+use shared::{ config, write_layout };
+
+#[test]
+fn lookup() {
+    write_layout("lookup_cangjie", &config(""), "character,sequence\n日,a\n日,abcd\n日,ab\n日,ba\n明,ab\n");
+    write_layout("lookup_pinyin", &config(""), "character,sequence\n日,ri\n月,yue\n");
+    let dir = write_layout("lookup_other", &config(""), "character,sequence\n木,d\n");
+    let engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("lookup_cangjie"),
+        layouts: vec![String::from("lookup_cangjie"), String::from("lookup_pinyin"), String::from("lookup_other")],
+        dir,
+        data_dir: None,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    // shortest first, those as long in table order, and only from layouts which have the text
+    assert_eq!(engine.lookup("日"), [("lookup_cangjie", vec!["a", "ab", "ba", "abcd"]), ("lookup_pinyin", vec!["ri"])]);
+    assert_eq!(engine.lookup("月"), [("lookup_pinyin", vec!["yue"])]);
+    assert_eq!(engine.lookup("木"), [("lookup_other", vec!["d"])]);
+    assert!(engine.lookup("日月").is_empty());
+}
@@ -0,0 +1,36 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse, Candidates };
+use shared::{ code, write_layout };
+
+const CONFIG: &str = "{ specs: { 22: [ BACKSPACE ], 65: [ COMMIT ] }, keys: { 38: [ a ], 39: [ s ], 40: [ d ], 41: [ f ] } }";
+
+fn engine(id: &str, table: &str) -> AnkraEngine {
+    AnkraEngine::new(AnkraConfig {
+        id: id.to_string(),
+        dir: write_layout(id, CONFIG, table),
+        data_dir: None,
+        candidate_list: true,
+        ..AnkraConfig::default()
+    }).unwrap()
+}
+
+fn candidates(response: AnkraResponse) -> Candidates {
+    match response {
+        AnkraResponse::Candidates(c) => c,
+        response => panic!("{:?}", response),
+    }
+}
+
+#[test]
+fn backspace_history() {
+    let mut engine = engine("backspace_history", "character,sequence\n日,a\n曰,a\n昌,aa\n明,as\n月,s\n");
+    let first = candidates(engine.on_key_press(code('a')));
+    assert_eq!(first.total, 4);
+
+    assert_eq!(candidates(engine.on_key_press(code('a'))).total, 1);
+    assert_eq!(candidates(engine.on_key_press(22)), first);
+    assert_eq!(candidates(engine.on_key_press(code('s'))).total, 1);
+    assert_eq!(candidates(engine.on_key_press(22)), first);
+}
@@ -0,0 +1,91 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, config, write_layout };
+
+const TABLE: &str = "character,sequence\n一,a\n二,a\n三,a\n四,a\n五,a\n六,a\n七,a\n";
+
+// listing candidates rather than suggesting the selected one
+fn layout(id: &str, options: &str) -> AnkraEngine {
+    let dir = write_layout(id, &config(options), TABLE);
+    AnkraEngine::new(AnkraConfig { id: id.to_string(), dir, data_dir: None, candidate_list: true, ..AnkraConfig::default() }).unwrap()
+}
+
+// the current page and the candidate selected on it
+fn page(engine: &mut AnkraEngine, key: u16, level: u16) -> (String, usize) {
+    engine.set_level(level);
+    let response = engine.on_key_press(key);
+    engine.set_level(0);
+    match response {
+        AnkraResponse::Candidates(candidates) => {
+            assert_eq!(candidates.total, 7);
+            (candidates.candidates.iter().map(|c| &*c.text).collect(), candidates.selected)
+        },
+        response => panic!("expected candidates, got {:?}", response),
+    }
+}
+
+#[test]
+fn page_up_down() {
+    let mut engine = layout("paging", "page_size: 3");
+    assert_eq!(page(&mut engine, code('a'), 0), (String::from("一二三"), 0));
+    assert_eq!(page(&mut engine, 117, 0), (String::from("四五六"), 0));
+    assert_eq!(page(&mut engine, 117, 0), (String::from("七"), 0));
+    // nothing past the last page, nor before the first
+    assert_eq!(page(&mut engine, 117, 0), (String::from("七"), 0));
+    assert_eq!(page(&mut engine, 112, 0), (String::from("四五六"), 0));
+    assert_eq!(page(&mut engine, 112, 0), (String::from("一二三"), 0));
+    assert_eq!(page(&mut engine, 112, 0), (String::from("一二三"), 0));
+}
+
+#[test]
+fn select_on_page() {
+    let mut engine = layout("paging_select", "page_size: 3");
+    engine.on_key_press(code('a'));
+    assert_eq!(page(&mut engine, 117, 0), (String::from("四五六"), 0));
+    // SELECT(n) counts from the start of the page, up to its size
+    assert_eq!(page(&mut engine, 12, 0), (String::from("四五六"), 2));
+    assert_eq!(page(&mut engine, 11, 0), (String::from("四五六"), 1));
+    assert_eq!(page(&mut engine, 13, 0), (String::from("四五六"), 1));
+
+    // nor past the candidates of a short page
+    assert_eq!(page(&mut engine, 117, 0), (String::from("七"), 0));
+    assert_eq!(page(&mut engine, 11, 0), (String::from("七"), 0));
+    assert_eq!(page(&mut engine, 112, 0), (String::from("四五六"), 0));
+    assert_eq!(page(&mut engine, 11, 0), (String::from("四五六"), 1));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("五")));
+}
+
+#[test]
+fn navigation_cross() {
+    let mut engine = layout("paging_cross", "page_size: 3, navigation: cross");
+    engine.on_key_press(code('a'));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 1));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 2));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("四五六"), 0));
+    assert_eq!(page(&mut engine, 23, 1), (String::from("一二三"), 2));
+    assert_eq!(page(&mut engine, 112, 0), (String::from("一二三"), 0));
+    // no further than the first and last candidates
+    assert_eq!(page(&mut engine, 23, 1), (String::from("一二三"), 0));
+    engine.on_key_press(117);
+    engine.on_key_press(117);
+    assert_eq!(page(&mut engine, 23, 0), (String::from("七"), 0));
+}
+
+#[test]
+fn navigation_wrap() {
+    let mut engine = layout("paging_wrap", "page_size: 3, navigation: wrap");
+    engine.on_key_press(code('a'));
+    assert_eq!(page(&mut engine, 23, 1), (String::from("一二三"), 2));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 0));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 1));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 2));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("一二三"), 0));
+
+    // a page shorter than page_size wraps within what it has
+    engine.on_key_press(117);
+    assert_eq!(page(&mut engine, 117, 0), (String::from("七"), 0));
+    assert_eq!(page(&mut engine, 23, 0), (String::from("七"), 0));
+    assert_eq!(page(&mut engine, 23, 1), (String::from("七"), 0));
+}
@@ -0,0 +1,40 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse::{ Commit, Suggest };
+use shared::{ code, layout, test_input_impl };
+
+// a phrase, a ZWJ sequence and a letter with a combining accent
+const TABLE: &str = "character,sequence\n我想,hidp\n我,hi\n👨\u{200d}👩\u{200d}👧,fam\ne\u{301},ee\n";
+
+fn s(text: &str) -> String {
+    String::from(text)
+}
+
+#[test]
+fn phrase() {
+    test_input_impl(layout("phrase", "", TABLE), &[
+        (code('h'), Suggest(s("我想"))),
+        (code('i'), Suggest(s("我想"))),
+        (code('d'), Suggest(s("我想"))),
+        (code('p'), Suggest(s("我想"))),
+        (65, Commit(s("我想"))),
+        (code('h'), Suggest(s("我想"))),
+        (code('i'), Suggest(s("我想"))),
+        (23, Suggest(s("我"))),
+        (65, Commit(s("我"))),
+    ]);
+}
+
+#[test]
+fn grapheme() {
+    test_input_impl(layout("grapheme", "", TABLE), &[
+        (code('f'), Suggest(s("👨\u{200d}👩\u{200d}👧"))),
+        (code('a'), Suggest(s("👨\u{200d}👩\u{200d}👧"))),
+        (code('m'), Suggest(s("👨\u{200d}👩\u{200d}👧"))),
+        (65, Commit(s("👨\u{200d}👩\u{200d}👧"))),
+        (code('e'), Suggest(s("e\u{301}"))),
+        (code('e'), Suggest(s("e\u{301}"))),
+        (65, Commit(s("e\u{301}"))),
+    ]);
+}
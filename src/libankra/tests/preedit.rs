@@ -0,0 +1,71 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse, Preedit, PreeditSegment };
+use shared::{ code, config, write_layout };
+
+fn layout(id: &str, preedit: &str) -> AnkraEngine {
+    let options = format!("preedit: {:?}", preedit);
+    let dir = write_layout(id, &config(&options), "character,sequence\n日,ab\n曰,ab\n");
+    AnkraEngine::new(AnkraConfig { id: id.to_string(), dir, data_dir: None, candidate_list: true, ..AnkraConfig::default() }).unwrap()
+}
+
+fn preedit(engine: &mut AnkraEngine, key: u16) -> Preedit {
+    match engine.on_key_press(key) {
+        AnkraResponse::Candidates(candidates) => candidates.preedit,
+        response => panic!("expected candidates, got {:?}", response),
+    }
+}
+
+#[test]
+fn preedit_template() {
+    let mut engine = layout("preedit_template", "[{keys}] {candidate} {position}");
+    engine.on_key_press(code('a'));
+    let p = preedit(&mut engine, code('b'));
+    assert_eq!(p.segments, [
+        PreeditSegment::Text(String::from("[")),
+        PreeditSegment::Keys(String::from("ab")),
+        PreeditSegment::Text(String::from("] ")),
+        PreeditSegment::Candidate(String::from("日")),
+        PreeditSegment::Text(String::from(" ")),
+        PreeditSegment::Position(String::from("(1/2)")),
+    ]);
+
+    // covering the candidate, in bytes
+    assert_eq!(p.cursor, Some((5, 8)));
+
+    let p = preedit(&mut engine, 23);
+    assert_eq!(p.text(), "[ab] 曰 (2/2)");
+    assert_eq!(p.cursor, Some((5, 8)));
+}
+
+#[test]
+fn preedit_default() {
+    let mut engine = layout("preedit_default", "{candidate}");
+    let p = preedit(&mut engine, code('a'));
+    assert_eq!(p.text(), "日");
+    assert_eq!(p.cursor, Some((0, 3)));
+}
+
+#[test]
+fn preedit_cursor() {
+    // {cursor} places a caret rather than covering the candidate
+    let mut engine = layout("preedit_cursor", "{keys}{cursor} {candidate}");
+    let p = preedit(&mut engine, code('a'));
+    assert_eq!(p.text(), "a 日");
+    assert_eq!(p.cursor, Some((1, 1)));
+
+    let mut engine = layout("preedit_cursor_multibyte", "「{candidate}」{cursor}");
+    let p = preedit(&mut engine, code('a'));
+    assert_eq!(p.text(), "「日」");
+    assert_eq!(p.cursor, Some((9, 9)));
+}
+
+#[test]
+fn preedit_unknown_field() {
+    // left as typed, and without a candidate nothing is covered
+    let mut engine = layout("preedit_unknown", "{keys} {unknown} {keys");
+    let p = preedit(&mut engine, code('a'));
+    assert_eq!(p.text(), "a {unknown} {keys");
+    assert_eq!(p.cursor, None);
+}
@@ -0,0 +1,49 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, config, write_layout };
+
+const TABLE: &str = "character,sequence,weight\n木,abc,9\n長,ab,1\n日,a,5\n月,a,\n";
+
+// the candidates listed for `a` under `ranking`
+fn ranked(id: &str, ranking: &str, table: &str) -> String {
+    let dir = write_layout(id, &config(&format!("ranking: {}", ranking)), table);
+    let mut engine = AnkraEngine::new(AnkraConfig { id: id.to_string(), dir, data_dir: None, candidate_list: true, ..AnkraConfig::default() }).unwrap();
+    match engine.on_key_press(code('a')) {
+        AnkraResponse::Candidates(candidates) => candidates.candidates.iter().map(|c| &*c.text).collect(),
+        response => panic!("expected candidates, got {:?}", response),
+    }
+}
+
+#[test]
+fn table_order() {
+    assert_eq!(ranked("ranking_table_order", "table_order", TABLE), "木長日月");
+}
+
+#[test]
+fn weight() {
+    // a missing weight counts as 0
+    assert_eq!(ranked("ranking_weight", "weight", TABLE), "木日長月");
+    let frequency = TABLE.replace("weight", "frequency");
+    assert_eq!(ranked("ranking_frequency", "weight", &frequency), "木日長月");
+
+    // without any weights the table order is kept
+    let unweighted = "character,sequence\n木,abc\n長,ab\n日,a\n月,a\n";
+    assert_eq!(ranked("ranking_unweighted", "weight", unweighted), "木長日月");
+}
+
+#[test]
+fn exact_first() {
+    assert_eq!(ranked("ranking_exact_first", "exact_first", TABLE), "日月木長");
+}
+
+#[test]
+fn shortest_first() {
+    assert_eq!(ranked("ranking_shortest_first", "shortest_first", TABLE), "日月長木");
+}
+
+#[test]
+fn codepoint() {
+    assert_eq!(ranked("ranking_codepoint", "codepoint", TABLE), "日月木長");
+}
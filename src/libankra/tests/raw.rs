@@ -0,0 +1,59 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse::{ Commit, Empty, Suggest, Undefined };
+use shared::{ code, layout, test_input_impl };
+
+const TABLE: &str = "character,sequence\n日,ab\n";
+const LABELS: &str = r#"labels: { a: "ㄇ", b: "ㄖ" }"#;
+
+fn s(text: &str) -> String {
+    String::from(text)
+}
+
+#[test]
+fn commit_raw() {
+    // labelled keys are committed as their label
+    test_input_impl(layout("raw_commit", LABELS, TABLE), &[
+        (code('a'), Suggest(s("日"))),
+        (code('b'), Suggest(s("日"))),
+        (36, Commit(s("ㄇㄖ"))),
+        // nothing typed, nothing to commit
+        (36, Undefined),
+    ]);
+}
+
+#[test]
+fn no_candidate_discard() {
+    test_input_impl(layout("raw_discard", "no_candidate: discard", TABLE), &[
+        (code('a'), Suggest(s("日"))),
+        (code('z'), Empty),
+        (code('a'), Suggest(s("日"))),
+        (code('b'), Suggest(s("日"))),
+        (65, Commit(s("日"))),
+    ]);
+}
+
+#[test]
+fn no_candidate_raw() {
+    let options = format!("no_candidate: raw, {}", LABELS);
+    test_input_impl(layout("raw_no_candidate", &options, TABLE), &[
+        (code('a'), Suggest(s("日"))),
+        (code('z'), Suggest(s("ㄇz"))),
+        (code('b'), Suggest(s("ㄇzㄖ"))),
+        (65, Commit(s("ㄇzㄖ"))),
+    ]);
+}
+
+#[test]
+fn no_candidate_compose() {
+    test_input_impl(layout("raw_compose", "no_candidate: compose", TABLE), &[
+        (code('a'), Suggest(s("日"))),
+        (code('z'), Suggest(s("az"))),
+        // COMMIT leaves it composing
+        (65, Suggest(s("az"))),
+        (22, Suggest(s("日"))),
+        (code('b'), Suggest(s("日"))),
+        (65, Commit(s("日"))),
+    ]);
+}
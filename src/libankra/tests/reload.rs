@@ -0,0 +1,34 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, config, layout, write_layout };
+
+#[test]
+fn reload() {
+    let mut engine = layout("reload", "", "character,sequence\n日,a\n");
+    engine.on_key_press(code('a'));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("日")));
+
+    // a broken config is reported, and the old table keeps answering
+    let dir = write_layout("reload", "{ specs: {", "character,sequence\n月,a\n");
+    assert!(engine.reload().is_err());
+    engine.on_key_press(code('a'));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("日")));
+
+    std::fs::write(dir.join("reload").join("config.zm"), config("")).unwrap();
+    assert!(engine.reload().is_ok());
+    engine.on_key_press(code('a'));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("月")));
+}
+
+#[test]
+fn reload_repeat() {
+    let mut engine = layout("reload_repeat", "", "character,sequence\n日,a\n");
+    engine.on_key_press(code('a'));
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("日")));
+
+    // what was last committed can still be repeated
+    assert!(engine.reload().is_ok());
+    assert_eq!(engine.on_key_press(49), AnkraResponse::Commit(String::from("日")));
+}
@@ -0,0 +1,38 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse::{ Commit, Suggest, Undefined };
+use shared::{ code, layout, test_input_impl };
+
+const TABLE: &str = "character,sequence\n日,a\n月,b\n";
+
+fn s(text: &str) -> String {
+    String::from(text)
+}
+
+#[test]
+fn repeat() {
+    test_input_impl(layout("repeat", "", TABLE), &[
+        // nothing committed yet, the key is left to the client
+        (49, Undefined),
+        (code('a'), Suggest(s("日"))),
+        (65, Commit(s("日"))),
+        (49, Commit(s("日"))),
+        (49, Commit(s("日"))),
+        // ignored while composing
+        (code('b'), Suggest(s("月"))),
+        (49, Suggest(s("月"))),
+        (65, Commit(s("月"))),
+        (49, Commit(s("月"))),
+    ]);
+}
+
+#[test]
+fn repeat_mark() {
+    test_input_impl(layout("repeat_mark", r#"repeat_mark: Some("々")"#, TABLE), &[
+        (49, Commit(s("々"))),
+        (code('a'), Suggest(s("日"))),
+        (65, Commit(s("日"))),
+        (49, Commit(s("々"))),
+    ]);
+}
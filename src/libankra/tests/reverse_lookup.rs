@@ -0,0 +1,88 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraResponse };
+use shared::{ code, config, write_layout };
+
+fn load(id: &str) -> AnkraEngine {
+    write_layout("reverse_lookup_pinyin", &config(""), "character,sequence\n想,xiang\n象,xiang\n日,ri\n");
+    let options = r#"reverse_lookup: Some("reverse_lookup_pinyin")"#;
+    AnkraEngine::new(AnkraConfig {
+        id: id.to_string(),
+        dir: write_layout(id, &config(options), "character,sequence\n日,a\n想,dup\n"),
+        data_dir: None,
+        candidate_list: true,
+        ..AnkraConfig::default()
+    }).unwrap()
+}
+
+fn press(engine: &mut AnkraEngine, keys: &str) -> AnkraResponse {
+    keys.chars().map(|c| engine.on_key_press(code(c))).last().unwrap()
+}
+
+// REVERSE_LOOKUP is the third level of key 49
+fn enter(engine: &mut AnkraEngine) -> AnkraResponse {
+    engine.set_level(2);
+    let response = engine.on_key_press(49);
+    engine.set_level(0);
+    response
+}
+
+#[test]
+fn reverse_lookup() {
+    let mut engine = load("reverse_lookup");
+    assert_eq!(enter(&mut engine), AnkraResponse::Empty);
+
+    // typed through the pinyin layout, annotated with the codes of this one
+    match press(&mut engine, "xiang") {
+        AnkraResponse::Candidates(c) => {
+            let shown: Vec<(&str, &str)> = c.candidates.iter().map(|c| (&*c.text, &*c.annotation)).collect();
+            assert_eq!(shown, [("想", "dup"), ("象", "")]);
+        },
+
+        response => panic!("{:?}", response),
+    }
+
+    // back to this layout once committed
+    assert_eq!(engine.on_key_press(65), AnkraResponse::Commit(String::from("想")));
+    match press(&mut engine, "a") {
+        AnkraResponse::Candidates(c) => assert_eq!(c.text(), Some("日")),
+        response => panic!("{:?}", response),
+    }
+}
+
+#[test]
+fn reverse_lookup_leave() {
+    let mut engine = load("reverse_lookup_leave");
+
+    enter(&mut engine);
+    press(&mut engine, "x");
+    assert_eq!(engine.on_key_press(9), AnkraResponse::Empty);
+    match press(&mut engine, "a") {
+        AnkraResponse::Candidates(c) => assert_eq!(c.text(), Some("日")),
+        response => panic!("{:?}", response),
+    }
+
+    engine.on_key_press(9);
+    enter(&mut engine);
+    press(&mut engine, "r");
+    assert_eq!(engine.on_key_press(22), AnkraResponse::Empty);
+    match press(&mut engine, "a") {
+        AnkraResponse::Candidates(c) => assert_eq!(c.text(), Some("日")),
+        response => panic!("{:?}", response),
+    }
+}
+
+#[test]
+fn reverse_lookup_missing() {
+    let dir = write_layout("reverse_lookup_missing", &config(r#"reverse_lookup: Some("missing")"#), "character,sequence\n日,a\n");
+    let mut engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("reverse_lookup_missing"),
+        dir,
+        data_dir: None,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    assert_eq!(enter(&mut engine), AnkraResponse::Undefined);
+    assert_eq!(press(&mut engine, "a"), AnkraResponse::Suggest(String::from("日")));
+}
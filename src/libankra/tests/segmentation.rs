@@ -0,0 +1,48 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout, test_input_impl };
+
+#[test]
+fn segmentation() {
+    let table = "character,sequence\n我,hqi\n手,q\n想,dup\n";
+    test_input_impl(layout("segmentation", "segmentation: true", table), &[
+        (code('h'), AnkraResponse::Suggest(String::from("我"))),
+        (code('q'), AnkraResponse::Suggest(String::from("我"))),
+        (code('i'), AnkraResponse::Suggest(String::from("我"))),
+        (code('d'), AnkraResponse::Suggest(String::from("我想"))),
+        (code('u'), AnkraResponse::Suggest(String::from("我想"))),
+        (code('p'), AnkraResponse::Suggest(String::from("我想"))),
+        (65, AnkraResponse::Commit(String::from("我想"))),
+    ])
+}
+
+// du is no complete code, so it can't be followed by another piece
+#[test]
+fn segmentation_partial() {
+    let table = "character,sequence\n想,dup\n";
+    test_input_impl(layout("segmentation_partial", "segmentation: true", table), &[
+        (code('d'), AnkraResponse::Suggest(String::from("想"))),
+        (code('u'), AnkraResponse::Suggest(String::from("想"))),
+        (code('p'), AnkraResponse::Suggest(String::from("想"))),
+        (code('d'), AnkraResponse::Suggest(String::from("想想"))),
+        (65, AnkraResponse::Commit(String::from("想想"))),
+
+        (code('d'), AnkraResponse::Suggest(String::from("想"))),
+        (code('u'), AnkraResponse::Suggest(String::from("想"))),
+        (code('d'), AnkraResponse::Empty),
+    ])
+}
+
+// abc splits into a|bc or ab|c, the heavier entries win
+#[test]
+fn segmentation_weight() {
+    let table = "character,sequence,weight\n一,a,1\n二,bc,1\n三,ab,5\n四,c,5\n";
+    test_input_impl(layout("segmentation_weight", "segmentation: true", table), &[
+        (code('a'), AnkraResponse::Suggest(String::from("一"))),
+        (code('b'), AnkraResponse::Suggest(String::from("三"))),
+        (code('c'), AnkraResponse::Suggest(String::from("三四"))),
+        (65, AnkraResponse::Commit(String::from("三四"))),
+    ])
+}
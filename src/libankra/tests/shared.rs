@@ -1,4 +1,6 @@
-use ankra::{ AnkraEngine, AnkraResponse };
+use ankra::{ AnkraEngine, AnkraConfig, AnkraResponse };
+use std::fs::{ create_dir_all, write };
+use std::path::PathBuf;
 
 #[track_caller]
 pub fn test_input_impl(mut engine: AnkraEngine, keys: &[(u16, AnkraResponse)]) {
@@ -13,6 +15,7 @@ pub fn test_input_impl(mut engine: AnkraEngine, keys: &[(u16, AnkraResponse)]) {
 #[track_caller]
 pub fn test_input_with_level_impl(mut engine: AnkraEngine, keys: &[(u16, u16, AnkraResponse)]) {
     for (key, level, response) in keys.iter() {
+        engine.set_level(level.to_owned());
         let rep = engine.on_key_press(key.to_owned());
         eprintln!("Key: {:?}, Level: {:?}, Rep: {:?}", key, level, rep);
         assert_eq!(&rep, response);
@@ -30,16 +33,18 @@ macro_rules! define_layout_test {
         fn test_input(keys: &[(u16, AnkraResponse)]) {
             let context = AnkraEngine::new(AnkraConfig { 
                 id: $layout.to_string(),
+                data_dir: None,
                 ..AnkraConfig::default()
-            });
+            }).unwrap();
             test_input_impl(context, keys);
         }
 
         fn test_input_with_level(keys: &[(u16, u16, AnkraResponse)]) {
             let context = AnkraEngine::new(AnkraConfig { 
                 id: $layout.to_string(),
+                data_dir: None,
                 ..AnkraConfig::default()
-            });
+            }).unwrap();
             test_input_with_level_impl(context, keys);
         }
     };
@@ -47,4 +52,46 @@ macro_rules! define_layout_test {
     ($layout:expr) => {
         define_layout_test!($layout);
     };
-}
\ No newline at end of file
+}
+// keys of example/basic, a to z
+const KEYS: [(u16, char); 26] = [
+    (24, 'q'), (25, 'w'), (26, 'e'), (27, 'r'), (28, 't'), (29, 'y'), (30, 'u'), (31, 'i'), (32, 'o'),
+    (33, 'p'), (38, 'a'), (39, 's'), (40, 'd'), (41, 'f'), (42, 'g'), (43, 'h'), (44, 'j'), (45, 'k'),
+    (46, 'l'), (52, 'z'), (53, 'x'), (54, 'c'), (55, 'v'), (56, 'b'), (57, 'n'), (58, 'm'),
+];
+
+const SPECS: &str = r#"9: [ ESCAPE ], 22: [ BACKSPACE ], 23: [ NEXT, PREV ], 65: [ COMMIT ], 36: [ COMMIT_RAW ],
+    61: [ WILDCARD ], 51: [ WILDCARD_ANY ], 60: [ INSERT("。") ], 49: [ REPEAT, CYCLE_LAYOUT, REVERSE_LOOKUP ], 50: [ TOGGLE_ASCII ],
+    112: [ PAGE_UP ], 117: [ PAGE_DOWN ],
+    10: [ SELECT(1) ], 11: [ SELECT(2) ], 12: [ SELECT(3) ], 13: [ SELECT(4) ], 14: [ SELECT(5) ],
+    15: [ SELECT(6) ], 16: [ SELECT(7) ], 17: [ SELECT(8) ], 18: [ SELECT(9) ]"#;
+
+// keycode typing `c` in layouts made by `layout`
+#[allow(dead_code)]
+pub fn code(c: char) -> u16 {
+    KEYS.iter().find(|(_, k)| *k==c).expect("no such key").0
+}
+
+// write a layout of its own, returning the directory it was written in
+#[allow(dead_code)]
+pub fn write_layout(id: &str, config: &str, table: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("ankra-tests");
+    create_dir_all(dir.join(id)).unwrap();
+    write(dir.join(id).join("config.zm"), config).unwrap();
+    write(dir.join(id).join("table.csv"), table).unwrap();
+    dir
+}
+
+// config with the specs and keys of example/basic, `options` being further config fields
+#[allow(dead_code)]
+pub fn config(options: &str) -> String {
+    let keys: Vec<String> = KEYS.iter().map(|(code, c)| format!("{}: [ {} ]", code, c)).collect();
+    let options = if options.is_empty() { String::new() } else { format!(", {}", options) };
+    format!("{{ specs: {{ {} }}, keys: {{ {} }}{} }}", SPECS, keys.join(", "), options)
+}
+
+#[allow(dead_code)]
+pub fn layout(id: &str, options: &str, table: &str) -> AnkraEngine {
+    let dir = write_layout(id, &config(options), table);
+    AnkraEngine::new(AnkraConfig { id: id.to_string(), dir, data_dir: None, ..AnkraConfig::default() }).unwrap()
+}
@@ -0,0 +1,62 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraEngine, AnkraConfig, AnkraResponse };
+use shared::{ code, layout, test_input_impl, write_layout };
+
+const TABLE: &str = "character,sequence\n日,a\n曰,a\n昌,aa\n月,b\n";
+
+#[test]
+fn unknown_spec() {
+    let config = |spec: &str| format!("{{ specs: {{ 65: [ {} ] }}, keys: {{ 24: [ q ] }} }}", spec);
+    let load = |id: &str, spec: &str| AnkraEngine::new(AnkraConfig {
+        id: id.to_string(),
+        dir: write_layout(id, &config(spec), TABLE),
+        data_dir: None,
+        ..AnkraConfig::default()
+    });
+
+    match load("unknown_spec", "CLEAR") {
+        Err(e) => assert!(e.to_string().contains("unknown_spec/config.zm"), "{}", e),
+        Ok(_) => panic!("loaded a layout with an unknown spec"),
+    }
+
+    assert!(load("known_spec", "COMMIT").is_ok());
+}
+
+#[test]
+fn select() {
+    test_input_impl(layout("select", "", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (11, AnkraResponse::Suggest(String::from("曰"))), // SELECT(2)
+        (18, AnkraResponse::Suggest(String::from("曰"))), // SELECT(9), past the candidates
+        (65, AnkraResponse::Commit(String::from("曰"))),
+    ])
+}
+
+#[test]
+fn insert() {
+    test_input_impl(layout("insert", "", TABLE), &[
+        (60, AnkraResponse::Commit(String::from("。"))),
+        (code('b'), AnkraResponse::Suggest(String::from("月"))),
+        (60, AnkraResponse::Commit(String::from("月。"))),
+    ])
+}
+
+// configs written before SELECT bound bare digits
+#[test]
+fn legacy_select() {
+    let config = "{ specs: { 65: [ COMMIT ], 10: [ 1 ], 11: [ 2 ] }, keys: { 38: [ a ] } }";
+    let engine = AnkraEngine::new(AnkraConfig {
+        id: String::from("legacy_select"),
+        dir: write_layout("legacy_select", config, TABLE),
+        data_dir: None,
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    test_input_impl(engine, &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (11, AnkraResponse::Suggest(String::from("曰"))),
+        (65, AnkraResponse::Commit(String::from("曰"))),
+    ])
+}
@@ -0,0 +1,67 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::{ AnkraConfig, AnkraEngine, AnkraError, AnkraResponse };
+use shared::{ code, config, write_layout };
+use std::fs::{ read_to_string, remove_dir_all };
+use std::path::PathBuf;
+
+// two layouts typing a differently, and where the current one gets written
+fn load(name: &str) -> (AnkraEngine, PathBuf) {
+    let first = format!("{}_first", name);
+    let second = format!("{}_second", name);
+    write_layout(&first, &config(""), "character,sequence\n日,a\n");
+    let dir = write_layout(&second, &config(""), "character,sequence\n月,a\n");
+
+    let data_dir = std::env::temp_dir().join("ankra-tests-data").join(name);
+    let _ = remove_dir_all(&data_dir);
+    let engine = AnkraEngine::new(AnkraConfig {
+        id: first.clone(),
+        layouts: vec![first, second],
+        dir,
+        data_dir: Some(data_dir.clone()),
+        ..AnkraConfig::default()
+    }).unwrap();
+
+    (engine, data_dir)
+}
+
+fn type_a(engine: &mut AnkraEngine) -> AnkraResponse {
+    engine.on_key_press(code('a'));
+    engine.on_key_press(65)
+}
+
+#[test]
+fn switch_layout() {
+    let (mut engine, data_dir) = load("switch");
+    assert_eq!(engine.layouts(), ["switch_first", "switch_second"]);
+    assert_eq!(engine.layout(), "switch_first");
+    assert_eq!(type_a(&mut engine), AnkraResponse::Commit(String::from("日")));
+
+    engine.switch_layout("switch_second").unwrap();
+    assert_eq!(engine.layout(), "switch_second");
+    assert_eq!(type_a(&mut engine), AnkraResponse::Commit(String::from("月")));
+    assert_eq!(read_to_string(data_dir.join("current_layout")).unwrap(), "switch_second");
+
+    // an unknown layout leaves the current one in use
+    assert!(matches!(engine.switch_layout("switch_missing"), Err(AnkraError::LayoutNotFound(id)) if id=="switch_missing"));
+    assert_eq!(engine.layout(), "switch_second");
+    assert_eq!(read_to_string(data_dir.join("current_layout")).unwrap(), "switch_second");
+}
+
+#[test]
+fn cycle_layout() {
+    let (mut engine, data_dir) = load("cycle");
+    engine.cycle_layout();
+    assert_eq!(engine.layout(), "cycle_second");
+    assert_eq!(read_to_string(data_dir.join("current_layout")).unwrap(), "cycle_second");
+
+    // wraps around to the first, here through CYCLE_LAYOUT, which drops what was typed
+    engine.on_key_press(code('a'));
+    engine.set_level(1);
+    assert_eq!(engine.on_key_press(49), AnkraResponse::Empty);
+    engine.set_level(0);
+    assert_eq!(engine.layout(), "cycle_first");
+    assert_eq!(read_to_string(data_dir.join("current_layout")).unwrap(), "cycle_first");
+    assert_eq!(type_a(&mut engine), AnkraResponse::Commit(String::from("日")));
+}
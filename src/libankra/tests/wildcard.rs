@@ -0,0 +1,42 @@
+#[allow(dead_code)]
+mod shared;
+
+use ankra::AnkraResponse;
+use shared::{ code, layout, test_input_impl };
+
+const TABLE: &str = "character,sequence\n日,a\n明,ab\n昌,aa\n晶,aaa\n月,b\n朋,bb\n";
+
+#[test]
+fn wildcard() {
+    test_input_impl(layout("wildcard", "ranking: table_order", TABLE), &[
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (61, AnkraResponse::Suggest(String::from("明"))),
+        (code('a'), AnkraResponse::Suggest(String::from("晶"))),
+        // back over the wildcard
+        (22, AnkraResponse::Suggest(String::from("明"))),
+        (22, AnkraResponse::Suggest(String::from("日"))),
+        (65, AnkraResponse::Commit(String::from("日"))),
+
+        (61, AnkraResponse::Suggest(String::from("日"))),
+        (code('b'), AnkraResponse::Suggest(String::from("明"))),
+        (23, AnkraResponse::Suggest(String::from("朋"))),
+        (65, AnkraResponse::Commit(String::from("朋"))),
+    ])
+}
+
+#[test]
+fn wildcard_any() {
+    test_input_impl(layout("wildcard_any", "ranking: table_order", TABLE), &[
+        (code('b'), AnkraResponse::Suggest(String::from("月"))),
+        (51, AnkraResponse::Suggest(String::from("月"))),
+        (23, AnkraResponse::Suggest(String::from("朋"))),
+        (65, AnkraResponse::Commit(String::from("朋"))),
+
+        (51, AnkraResponse::Suggest(String::from("日"))),
+        (code('a'), AnkraResponse::Suggest(String::from("日"))),
+        (code('a'), AnkraResponse::Suggest(String::from("昌"))),
+        (code('a'), AnkraResponse::Suggest(String::from("晶"))),
+        (22, AnkraResponse::Suggest(String::from("昌"))),
+        (65, AnkraResponse::Commit(String::from("昌"))),
+    ])
+}